@@ -0,0 +1,275 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The AEADs run by the guests, so that the host can reproduce their ciphertexts.
+
+use std::{fmt, str::FromStr};
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Nonce, Payload},
+    Aes128Gcm, Aes256Gcm,
+};
+use chacha20poly1305::ChaCha20Poly1305;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::JournalError;
+
+/// Length of the nonce in bytes. All supported algorithms take a 96-bit nonce.
+pub const NONCE_LEN: usize = 12;
+
+/// The AEAD a document is encrypted with. Journals commit to it by its `u8` identifier.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[repr(u8)]
+pub enum Algorithm {
+    #[default]
+    Aes256Gcm = 1,
+    Aes128Gcm = 2,
+    ChaCha20Poly1305 = 3,
+}
+
+impl Algorithm {
+    /// Length of the algorithm's key in bytes.
+    pub fn key_len(self) -> usize {
+        match self {
+            Algorithm::Aes256Gcm | Algorithm::ChaCha20Poly1305 => 32,
+            Algorithm::Aes128Gcm => 16,
+        }
+    }
+}
+
+impl TryFrom<u8> for Algorithm {
+    type Error = JournalError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Algorithm::Aes256Gcm),
+            2 => Ok(Algorithm::Aes128Gcm),
+            3 => Ok(Algorithm::ChaCha20Poly1305),
+            _ => Err(JournalError::UnknownAlgorithm(value)),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Algorithm::Aes256Gcm => "aes-256-gcm",
+            Algorithm::Aes128Gcm => "aes-128-gcm",
+            Algorithm::ChaCha20Poly1305 => "chacha20-poly1305",
+        })
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = CipherError;
+
+    /// Parse the names printed by `Display`: `aes-256-gcm`, `aes-128-gcm` or
+    /// `chacha20-poly1305`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "aes-256-gcm" => Ok(Algorithm::Aes256Gcm),
+            "aes-128-gcm" => Ok(Algorithm::Aes128Gcm),
+            "chacha20-poly1305" => Ok(Algorithm::ChaCha20Poly1305),
+            _ => Err(CipherError::UnknownAlgorithm(s.to_string())),
+        }
+    }
+}
+
+/// Encrypt `plaintext` with `algorithm`, authenticating `aad` along with it.
+///
+/// The result is the ciphertext followed by the 16-byte tag.
+pub fn encrypt(
+    algorithm: Algorithm,
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, CipherError> {
+    match algorithm {
+        Algorithm::Aes256Gcm => seal::<Aes256Gcm>(key, nonce, aad, plaintext),
+        Algorithm::Aes128Gcm => seal::<Aes128Gcm>(key, nonce, aad, plaintext),
+        Algorithm::ChaCha20Poly1305 => seal::<ChaCha20Poly1305>(key, nonce, aad, plaintext),
+    }
+}
+
+/// Decrypt a ciphertext produced by [encrypt], checking its tag and `aad`.
+pub fn decrypt(
+    algorithm: Algorithm,
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, CipherError> {
+    match algorithm {
+        Algorithm::Aes256Gcm => open::<Aes256Gcm>(key, nonce, aad, ciphertext),
+        Algorithm::Aes128Gcm => open::<Aes128Gcm>(key, nonce, aad, ciphertext),
+        Algorithm::ChaCha20Poly1305 => open::<ChaCha20Poly1305>(key, nonce, aad, ciphertext),
+    }
+}
+
+fn seal<A: Aead + KeyInit>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, CipherError> {
+    let cipher = A::new_from_slice(key).map_err(|_| CipherError::KeyLength(key.len()))?;
+    if nonce.len() != NONCE_LEN {
+        return Err(CipherError::NonceLength(nonce.len()));
+    }
+    cipher
+        .encrypt(
+            Nonce::<A>::from_slice(nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| CipherError::Aead)
+}
+
+fn open<A: Aead + KeyInit>(
+    key: &[u8],
+    nonce: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, CipherError> {
+    let cipher = A::new_from_slice(key).map_err(|_| CipherError::KeyLength(key.len()))?;
+    if nonce.len() != NONCE_LEN {
+        return Err(CipherError::NonceLength(nonce.len()));
+    }
+    cipher
+        .decrypt(
+            Nonce::<A>::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| CipherError::Aead)
+}
+
+/// SHA-256 of a plaintext, as committed by the guests.
+pub fn plaintext_hash(plaintext: &[u8]) -> [u8; 32] {
+    Sha256::digest(plaintext).into()
+}
+
+/// SHA-256 of a ciphertext, as committed by the guests.
+pub fn ciphertext_hash(ciphertext: &[u8]) -> [u8; 32] {
+    Sha256::digest(ciphertext).into()
+}
+
+/// The stored form of a ciphertext: the nonce followed by the ciphertext and tag.
+pub fn stored_ciphertext(nonce: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    let mut stored = Vec::with_capacity(nonce.len() + ciphertext.len());
+    stored.extend_from_slice(nonce);
+    stored.extend_from_slice(ciphertext);
+    stored
+}
+
+/// Errors returned by the AEAD helpers.
+#[derive(Debug)]
+pub enum CipherError {
+    /// The key has the wrong length for the algorithm, see [Algorithm::key_len].
+    KeyLength(usize),
+
+    /// The nonce is not [NONCE_LEN] bytes long.
+    NonceLength(usize),
+
+    /// The AEAD operation failed. For decryption, the key, nonce, AAD or ciphertext is wrong.
+    Aead,
+
+    /// A chunk follows the final chunk of a stream, or the stream has more than 2^32 chunks.
+    StreamEnded,
+
+    /// A name that is not one of `aes-256-gcm`, `aes-128-gcm` or `chacha20-poly1305`.
+    UnknownAlgorithm(String),
+}
+
+impl fmt::Display for CipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CipherError::KeyLength(len) => write!(f, "key of {len} bytes is the wrong length"),
+            CipherError::NonceLength(len) => {
+                write!(f, "nonce is {len} bytes, expected {NONCE_LEN}")
+            }
+            CipherError::Aead => write!(f, "AEAD operation failed"),
+            CipherError::StreamEnded => write!(f, "the stream has ended"),
+            CipherError::UnknownAlgorithm(name) => write!(f, "unknown algorithm '{name}'"),
+        }
+    }
+}
+
+impl std::error::Error for CipherError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aad_is_authenticated() {
+        let key = [7u8; 32];
+        let nonce = [1u8; NONCE_LEN];
+        let algorithm = Algorithm::Aes256Gcm;
+        let ciphertext = encrypt(algorithm, &key, &nonce, b"aad", b"{}").unwrap();
+        assert_eq!(ciphertext.len(), 2 + 16);
+        assert_ne!(
+            ciphertext,
+            encrypt(algorithm, &key, &nonce, b"other", b"{}").unwrap()
+        );
+        assert_eq!(
+            decrypt(algorithm, &key, &nonce, b"aad", &ciphertext).unwrap(),
+            b"{}"
+        );
+        assert!(matches!(
+            decrypt(algorithm, &key, &nonce, b"other", &ciphertext),
+            Err(CipherError::Aead)
+        ));
+        assert!(matches!(
+            encrypt(algorithm, &key[..16], &nonce, b"", b""),
+            Err(CipherError::KeyLength(16))
+        ));
+    }
+
+    #[test]
+    fn algorithms() {
+        let nonce = [1u8; NONCE_LEN];
+        for algorithm in [
+            Algorithm::Aes256Gcm,
+            Algorithm::Aes128Gcm,
+            Algorithm::ChaCha20Poly1305,
+        ] {
+            let key = vec![7u8; algorithm.key_len()];
+            let ciphertext = encrypt(algorithm, &key, &nonce, b"aad", b"{}").unwrap();
+            assert_eq!(
+                decrypt(algorithm, &key, &nonce, b"aad", &ciphertext).unwrap(),
+                b"{}"
+            );
+            assert_eq!(Algorithm::try_from(algorithm as u8).unwrap(), algorithm);
+            assert_eq!(
+                algorithm.to_string().parse::<Algorithm>().unwrap(),
+                algorithm
+            );
+        }
+        assert!(matches!(
+            encrypt(Algorithm::Aes128Gcm, &[7u8; 32], &nonce, b"", b""),
+            Err(CipherError::KeyLength(32))
+        ));
+        assert!(matches!(
+            Algorithm::try_from(0),
+            Err(JournalError::UnknownAlgorithm(0))
+        ));
+    }
+}
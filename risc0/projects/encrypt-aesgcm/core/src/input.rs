@@ -0,0 +1,153 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use json_validate_core::SchemaDraft;
+use serde::{Deserialize, Serialize};
+
+use crate::{Algorithm, KeySource, KEY_SALT_LEN};
+
+/// Input written by the host and read by the encrypt_aesgcm guest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EncryptInput {
+    /// The AEAD to use.
+    pub algorithm: Algorithm,
+
+    /// The key, derived in the guest.
+    pub key: KeySource,
+
+    /// Salt for the [key_commitment](crate::key_commitment).
+    pub key_salt: [u8; KEY_SALT_LEN],
+
+    /// Additional authenticated data, committed to the journal in the clear.
+    pub aad: Vec<u8>,
+
+    /// The document to encrypt.
+    pub plaintext: String,
+
+    /// The nonce, see [NONCE_LEN](crate::NONCE_LEN).
+    pub nonce: Vec<u8>,
+
+    /// Commit the ciphertext itself to the journal, not just its hash.
+    pub commit_ciphertext: bool,
+}
+
+/// Input written by the host and read by the decrypt guest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DecryptInput {
+    /// The AEAD to use.
+    pub algorithm: Algorithm,
+
+    /// The key, derived in the guest.
+    pub key: KeySource,
+
+    /// Salt for the [key_commitment](crate::key_commitment).
+    pub key_salt: [u8; KEY_SALT_LEN],
+
+    /// The nonce, see [NONCE_LEN](crate::NONCE_LEN).
+    pub nonce: Vec<u8>,
+
+    /// Additional authenticated data, committed to the journal in the clear.
+    pub aad: Vec<u8>,
+
+    /// The ciphertext followed by its tag.
+    pub ciphertext: Vec<u8>,
+}
+
+/// Input written by the host and read by the encrypt_validate guest.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SchemaEncryptInput {
+    /// The AEAD to use.
+    pub algorithm: Algorithm,
+
+    /// The key, derived in the guest.
+    pub key: KeySource,
+
+    /// Salt for the [key_commitment](crate::key_commitment).
+    pub key_salt: [u8; KEY_SALT_LEN],
+
+    /// The nonce, see [NONCE_LEN](crate::NONCE_LEN).
+    pub nonce: Vec<u8>,
+
+    /// Additional authenticated data, committed to the journal in the clear.
+    pub aad: Vec<u8>,
+
+    /// The JSON document to encrypt. It never leaves the guest.
+    pub plaintext: String,
+
+    /// The JSON schema the document must conform to.
+    pub schema: String,
+
+    /// Overrides the schema's `$schema` keyword when set.
+    pub draft: Option<SchemaDraft>,
+}
+
+/// Input written by the host and read by the encrypt_stream guest. The plaintext follows on
+/// stdin.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StreamEncryptInput {
+    /// The AEAD to use.
+    pub algorithm: Algorithm,
+
+    /// The key, derived in the guest.
+    pub key: KeySource,
+
+    /// Salt for the [key_commitment](crate::key_commitment).
+    pub key_salt: [u8; KEY_SALT_LEN],
+
+    /// The nonce prefix, see [STREAM_NONCE_PREFIX_LEN](crate::STREAM_NONCE_PREFIX_LEN).
+    pub nonce_prefix: Vec<u8>,
+
+    /// Additional authenticated data of every chunk, committed to the journal in the clear.
+    pub aad: Vec<u8>,
+
+    /// Plaintext length of every chunk but the last.
+    pub chunk_len: u32,
+}
+
+/// Input written by the host and read by the reencrypt guest.
+///
+/// The `from_` fields describe the existing ciphertext and the `to_` fields the new one. Both
+/// authenticate the same `aad`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReencryptInput {
+    /// The AEAD the ciphertext was encrypted with.
+    pub from_algorithm: Algorithm,
+
+    /// The key the ciphertext was encrypted under, derived in the guest.
+    pub from_key: KeySource,
+
+    /// Salt for the [key_commitment](crate::key_commitment) to the old key.
+    pub from_key_salt: [u8; KEY_SALT_LEN],
+
+    /// The nonce the ciphertext was encrypted under.
+    pub from_nonce: Vec<u8>,
+
+    /// The ciphertext followed by its tag.
+    pub ciphertext: Vec<u8>,
+
+    /// Additional authenticated data, committed to the journal in the clear.
+    pub aad: Vec<u8>,
+
+    /// The AEAD to re-encrypt with.
+    pub to_algorithm: Algorithm,
+
+    /// The key to re-encrypt under, derived in the guest.
+    pub to_key: KeySource,
+
+    /// Salt for the [key_commitment](crate::key_commitment) to the new key.
+    pub to_key_salt: [u8; KEY_SALT_LEN],
+
+    /// The nonce to re-encrypt under.
+    pub to_nonce: Vec<u8>,
+}
@@ -0,0 +1,674 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use alloy_sol_types::{sol, SolValue};
+use json_validate_core::SchemaDraft;
+use serde::{Deserialize, Serialize};
+
+use crate::Algorithm;
+
+/// Version of the journal layouts committed by the encrypt-aesgcm guests.
+///
+/// Bump this whenever a field is added, removed or reordered in a journal.
+pub const JOURNAL_VERSION: u8 = 4;
+
+sol! {
+    struct AbiEncryptJournal {
+        uint8 version;
+        uint8 algorithm;
+        bytes nonce;
+        bytes aad;
+        bytes32 plaintextHash;
+        bytes32 ciphertextHash;
+        bytes32 keyCommitment;
+        bytes ciphertext;
+    }
+
+    struct AbiDecryptJournal {
+        uint8 version;
+        uint8 algorithm;
+        bytes nonce;
+        bytes aad;
+        bytes32 ciphertextHash;
+        bytes32 plaintextHash;
+        bytes32 keyCommitment;
+    }
+
+    struct AbiSchemaEncryptJournal {
+        uint8 version;
+        uint8 algorithm;
+        bytes nonce;
+        bytes32 ciphertextHash;
+        bytes32 keyCommitment;
+        bytes aad;
+        bytes32 schemaHash;
+        uint8 draft;
+    }
+
+    struct AbiStreamEncryptJournal {
+        uint8 version;
+        uint8 algorithm;
+        bytes noncePrefix;
+        bytes aad;
+        uint32 chunkLen;
+        uint64 chunkCount;
+        bytes32 plaintextHash;
+        bytes32 ciphertextChain;
+        bytes32 keyCommitment;
+    }
+
+    struct AbiReencryptJournal {
+        uint8 version;
+        bytes aad;
+        uint8 fromAlgorithm;
+        bytes fromNonce;
+        bytes32 fromCiphertextHash;
+        bytes32 fromKeyCommitment;
+        uint8 toAlgorithm;
+        bytes toNonce;
+        bytes32 toCiphertextHash;
+        bytes32 toKeyCommitment;
+    }
+}
+
+/// The journal committed by the encrypt_aesgcm guest.
+///
+/// A receipt for this journal proves that the ciphertext with hash `ciphertext_hash` is the
+/// encryption with `algorithm`, under `nonce` and `aad`, of a document with hash
+/// `plaintext_hash`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EncryptJournal {
+    /// Layout version, always [JOURNAL_VERSION] for journals produced by this crate.
+    pub version: u8,
+
+    /// The AEAD the document was encrypted with.
+    pub algorithm: Algorithm,
+
+    /// The nonce.
+    pub nonce: Vec<u8>,
+
+    /// The additional authenticated data.
+    pub aad: Vec<u8>,
+
+    /// SHA-256 of the plaintext, see [plaintext_hash](crate::plaintext_hash).
+    pub plaintext_hash: [u8; 32],
+
+    /// SHA-256 of the ciphertext including its tag, see [ciphertext_hash](crate::ciphertext_hash).
+    pub ciphertext_hash: [u8; 32],
+
+    /// Commitment to the key, see [key_commitment](crate::key_commitment).
+    pub key_commitment: [u8; 32],
+
+    /// The ciphertext itself, when the host asked for it to be committed.
+    ///
+    /// Encoded as empty bytes when absent; a ciphertext is never empty since it ends in a tag.
+    pub ciphertext: Option<Vec<u8>>,
+}
+
+impl EncryptJournal {
+    /// Encode the journal as the Solidity ABI encoding of the `EncryptJournal` struct.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        AbiEncryptJournal {
+            version: self.version,
+            algorithm: self.algorithm as u8,
+            nonce: self.nonce.clone().into(),
+            aad: self.aad.clone().into(),
+            plaintextHash: self.plaintext_hash.into(),
+            ciphertextHash: self.ciphertext_hash.into(),
+            keyCommitment: self.key_commitment.into(),
+            ciphertext: self.ciphertext.clone().unwrap_or_default().into(),
+        }
+        .abi_encode()
+    }
+
+    /// Decode a journal produced by [EncryptJournal::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        let abi = AbiEncryptJournal::abi_decode(bytes, true).map_err(JournalError::Abi)?;
+        if abi.version != JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion(abi.version));
+        }
+        Ok(EncryptJournal {
+            version: abi.version,
+            algorithm: Algorithm::try_from(abi.algorithm)?,
+            nonce: abi.nonce.to_vec(),
+            aad: abi.aad.to_vec(),
+            plaintext_hash: abi.plaintextHash.into(),
+            ciphertext_hash: abi.ciphertextHash.into(),
+            key_commitment: abi.keyCommitment.into(),
+            ciphertext: (!abi.ciphertext.is_empty()).then(|| abi.ciphertext.to_vec()),
+        })
+    }
+
+    /// Whether `stored` is the [stored_ciphertext](crate::stored_ciphertext) this journal
+    /// commits to.
+    pub fn matches_stored(&self, stored: &[u8]) -> bool {
+        matches_stored(&self.nonce, &self.ciphertext_hash, stored)
+    }
+}
+
+/// The journal committed by the decrypt guest.
+///
+/// A receipt for this journal proves that the ciphertext with hash `ciphertext_hash` decrypts,
+/// under `nonce` and `aad` and the committed key, to a plaintext with hash `plaintext_hash`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DecryptJournal {
+    /// Layout version, always [JOURNAL_VERSION] for journals produced by this crate.
+    pub version: u8,
+
+    /// The AEAD the document was encrypted with.
+    pub algorithm: Algorithm,
+
+    /// The nonce.
+    pub nonce: Vec<u8>,
+
+    /// The additional authenticated data.
+    pub aad: Vec<u8>,
+
+    /// SHA-256 of the ciphertext including its tag, see [ciphertext_hash](crate::ciphertext_hash).
+    pub ciphertext_hash: [u8; 32],
+
+    /// SHA-256 of the plaintext, see [plaintext_hash](crate::plaintext_hash).
+    pub plaintext_hash: [u8; 32],
+
+    /// Commitment to the key, see [key_commitment](crate::key_commitment).
+    pub key_commitment: [u8; 32],
+}
+
+impl DecryptJournal {
+    /// Encode the journal as the Solidity ABI encoding of the `DecryptJournal` struct.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        AbiDecryptJournal {
+            version: self.version,
+            algorithm: self.algorithm as u8,
+            nonce: self.nonce.clone().into(),
+            aad: self.aad.clone().into(),
+            ciphertextHash: self.ciphertext_hash.into(),
+            plaintextHash: self.plaintext_hash.into(),
+            keyCommitment: self.key_commitment.into(),
+        }
+        .abi_encode()
+    }
+
+    /// Decode a journal produced by [DecryptJournal::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        let abi = AbiDecryptJournal::abi_decode(bytes, true).map_err(JournalError::Abi)?;
+        if abi.version != JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion(abi.version));
+        }
+        Ok(DecryptJournal {
+            version: abi.version,
+            algorithm: Algorithm::try_from(abi.algorithm)?,
+            nonce: abi.nonce.to_vec(),
+            aad: abi.aad.to_vec(),
+            ciphertext_hash: abi.ciphertextHash.into(),
+            plaintext_hash: abi.plaintextHash.into(),
+            key_commitment: abi.keyCommitment.into(),
+        })
+    }
+
+    /// Whether `stored` is the [stored_ciphertext](crate::stored_ciphertext) this journal
+    /// commits to.
+    pub fn matches_stored(&self, stored: &[u8]) -> bool {
+        matches_stored(&self.nonce, &self.ciphertext_hash, stored)
+    }
+}
+
+/// The journal committed by the encrypt_validate guest.
+///
+/// A receipt for this journal proves that the ciphertext with hash `ciphertext_hash` is the
+/// encryption with `algorithm`, under `aad`, of a JSON document conforming to the schema. The
+/// guest panics on a non-conforming document, so there is no receipt for one.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SchemaEncryptJournal {
+    /// Layout version, always [JOURNAL_VERSION] for journals produced by this crate.
+    pub version: u8,
+
+    /// The AEAD the document was encrypted with.
+    pub algorithm: Algorithm,
+
+    /// The nonce.
+    pub nonce: Vec<u8>,
+
+    /// SHA-256 of the ciphertext including its tag, see [ciphertext_hash](crate::ciphertext_hash).
+    pub ciphertext_hash: [u8; 32],
+
+    /// Commitment to the key, see [key_commitment](crate::key_commitment).
+    pub key_commitment: [u8; 32],
+
+    /// The additional authenticated data.
+    pub aad: Vec<u8>,
+
+    /// Commitment to the schema, see [schema_commitment](json_validate_core::schema_commitment).
+    pub schema_hash: [u8; 32],
+
+    /// The draft the schema was compiled against.
+    pub draft: SchemaDraft,
+}
+
+impl SchemaEncryptJournal {
+    /// Encode the journal as the Solidity ABI encoding of the `SchemaEncryptJournal` struct.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        AbiSchemaEncryptJournal {
+            version: self.version,
+            algorithm: self.algorithm as u8,
+            nonce: self.nonce.clone().into(),
+            ciphertextHash: self.ciphertext_hash.into(),
+            keyCommitment: self.key_commitment.into(),
+            aad: self.aad.clone().into(),
+            schemaHash: self.schema_hash.into(),
+            draft: self.draft as u8,
+        }
+        .abi_encode()
+    }
+
+    /// Decode a journal produced by [SchemaEncryptJournal::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        let abi = AbiSchemaEncryptJournal::abi_decode(bytes, true).map_err(JournalError::Abi)?;
+        if abi.version != JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion(abi.version));
+        }
+        Ok(SchemaEncryptJournal {
+            version: abi.version,
+            algorithm: Algorithm::try_from(abi.algorithm)?,
+            nonce: abi.nonce.to_vec(),
+            ciphertext_hash: abi.ciphertextHash.into(),
+            key_commitment: abi.keyCommitment.into(),
+            aad: abi.aad.to_vec(),
+            schema_hash: abi.schemaHash.into(),
+            draft: SchemaDraft::try_from(abi.draft)
+                .map_err(|_| JournalError::UnknownDraft(abi.draft))?,
+        })
+    }
+
+    /// Whether `stored` is the [stored_ciphertext](crate::stored_ciphertext) this journal
+    /// commits to.
+    pub fn matches_stored(&self, stored: &[u8]) -> bool {
+        matches_stored(&self.nonce, &self.ciphertext_hash, stored)
+    }
+}
+
+/// The journal committed by the encrypt_stream guest.
+///
+/// A receipt for this journal proves that the ciphertext chunks chained into `ciphertext_chain`
+/// are the STREAM encryption with `algorithm`, under `nonce_prefix` and `aad`, of a document with
+/// hash `plaintext_hash`, split into chunks of `chunk_len` bytes.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StreamEncryptJournal {
+    /// Layout version, always [JOURNAL_VERSION] for journals produced by this crate.
+    pub version: u8,
+
+    /// The AEAD the chunks were encrypted with.
+    pub algorithm: Algorithm,
+
+    /// The nonce prefix, see [stream_nonce](crate::stream_nonce).
+    pub nonce_prefix: Vec<u8>,
+
+    /// The additional authenticated data of every chunk.
+    pub aad: Vec<u8>,
+
+    /// Plaintext length of every chunk but the last.
+    pub chunk_len: u32,
+
+    /// Number of chunks, at least one.
+    pub chunk_count: u64,
+
+    /// SHA-256 of the whole plaintext, see [plaintext_hash](crate::plaintext_hash).
+    pub plaintext_hash: [u8; 32],
+
+    /// Hash chain over the ciphertext chunks, see [chain_hash](crate::chain_hash).
+    pub ciphertext_chain: [u8; 32],
+
+    /// Commitment to the key, see [key_commitment](crate::key_commitment).
+    pub key_commitment: [u8; 32],
+}
+
+impl StreamEncryptJournal {
+    /// Encode the journal as the Solidity ABI encoding of the `StreamEncryptJournal` struct.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        AbiStreamEncryptJournal {
+            version: self.version,
+            algorithm: self.algorithm as u8,
+            noncePrefix: self.nonce_prefix.clone().into(),
+            aad: self.aad.clone().into(),
+            chunkLen: self.chunk_len,
+            chunkCount: self.chunk_count,
+            plaintextHash: self.plaintext_hash.into(),
+            ciphertextChain: self.ciphertext_chain.into(),
+            keyCommitment: self.key_commitment.into(),
+        }
+        .abi_encode()
+    }
+
+    /// Decode a journal produced by [StreamEncryptJournal::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        let abi = AbiStreamEncryptJournal::abi_decode(bytes, true).map_err(JournalError::Abi)?;
+        if abi.version != JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion(abi.version));
+        }
+        Ok(StreamEncryptJournal {
+            version: abi.version,
+            algorithm: Algorithm::try_from(abi.algorithm)?,
+            nonce_prefix: abi.noncePrefix.to_vec(),
+            aad: abi.aad.to_vec(),
+            chunk_len: abi.chunkLen,
+            chunk_count: abi.chunkCount,
+            plaintext_hash: abi.plaintextHash.into(),
+            ciphertext_chain: abi.ciphertextChain.into(),
+            key_commitment: abi.keyCommitment.into(),
+        })
+    }
+
+    /// Whether `stored` is the nonce prefix followed by the ciphertext chunks this journal
+    /// commits to.
+    pub fn matches_stored(&self, stored: &[u8]) -> bool {
+        let Some((prefix, mut rest)) = stored.split_at_checked(self.nonce_prefix.len()) else {
+            return false;
+        };
+        if prefix != self.nonce_prefix || self.chunk_len == 0 {
+            return false;
+        }
+        // Every chunk but the last carries `chunk_len` bytes of plaintext and a 16-byte tag.
+        let chunk_len = self.chunk_len as usize + 16;
+        let mut chain = [0u8; 32];
+        let mut count = 0u64;
+        loop {
+            count += 1;
+            match rest.split_at_checked(chunk_len) {
+                Some((chunk, tail)) if !tail.is_empty() => {
+                    chain = crate::chain_hash(&chain, chunk);
+                    rest = tail;
+                }
+                _ => {
+                    chain = crate::chain_hash(&chain, rest);
+                    break;
+                }
+            }
+        }
+        count == self.chunk_count && chain == self.ciphertext_chain
+    }
+}
+
+/// The journal committed by the reencrypt guest.
+///
+/// A receipt for this journal proves that the ciphertext with hash `to_ciphertext_hash`, under
+/// the key committed to by `to_key_commitment`, decrypts to the same plaintext as the ciphertext
+/// with hash `from_ciphertext_hash` under the key committed to by `from_key_commitment`. Neither
+/// the plaintext nor its hash is committed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReencryptJournal {
+    /// Layout version, always [JOURNAL_VERSION] for journals produced by this crate.
+    pub version: u8,
+
+    /// The additional authenticated data of both ciphertexts.
+    pub aad: Vec<u8>,
+
+    /// The AEAD of the old ciphertext.
+    pub from_algorithm: Algorithm,
+
+    /// The nonce of the old ciphertext.
+    pub from_nonce: Vec<u8>,
+
+    /// SHA-256 of the old ciphertext including its tag.
+    pub from_ciphertext_hash: [u8; 32],
+
+    /// Commitment to the old key, see [key_commitment](crate::key_commitment).
+    pub from_key_commitment: [u8; 32],
+
+    /// The AEAD of the new ciphertext.
+    pub to_algorithm: Algorithm,
+
+    /// The nonce of the new ciphertext.
+    pub to_nonce: Vec<u8>,
+
+    /// SHA-256 of the new ciphertext including its tag.
+    pub to_ciphertext_hash: [u8; 32],
+
+    /// Commitment to the new key.
+    pub to_key_commitment: [u8; 32],
+}
+
+impl ReencryptJournal {
+    /// Encode the journal as the Solidity ABI encoding of the `ReencryptJournal` struct.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        AbiReencryptJournal {
+            version: self.version,
+            aad: self.aad.clone().into(),
+            fromAlgorithm: self.from_algorithm as u8,
+            fromNonce: self.from_nonce.clone().into(),
+            fromCiphertextHash: self.from_ciphertext_hash.into(),
+            fromKeyCommitment: self.from_key_commitment.into(),
+            toAlgorithm: self.to_algorithm as u8,
+            toNonce: self.to_nonce.clone().into(),
+            toCiphertextHash: self.to_ciphertext_hash.into(),
+            toKeyCommitment: self.to_key_commitment.into(),
+        }
+        .abi_encode()
+    }
+
+    /// Decode a journal produced by [ReencryptJournal::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        let abi = AbiReencryptJournal::abi_decode(bytes, true).map_err(JournalError::Abi)?;
+        if abi.version != JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion(abi.version));
+        }
+        Ok(ReencryptJournal {
+            version: abi.version,
+            aad: abi.aad.to_vec(),
+            from_algorithm: Algorithm::try_from(abi.fromAlgorithm)?,
+            from_nonce: abi.fromNonce.to_vec(),
+            from_ciphertext_hash: abi.fromCiphertextHash.into(),
+            from_key_commitment: abi.fromKeyCommitment.into(),
+            to_algorithm: Algorithm::try_from(abi.toAlgorithm)?,
+            to_nonce: abi.toNonce.to_vec(),
+            to_ciphertext_hash: abi.toCiphertextHash.into(),
+            to_key_commitment: abi.toKeyCommitment.into(),
+        })
+    }
+
+    /// Whether `stored` is the [stored_ciphertext](crate::stored_ciphertext) of the old
+    /// ciphertext.
+    pub fn matches_stored_from(&self, stored: &[u8]) -> bool {
+        matches_stored(&self.from_nonce, &self.from_ciphertext_hash, stored)
+    }
+
+    /// Whether `stored` is the [stored_ciphertext](crate::stored_ciphertext) of the new
+    /// ciphertext.
+    pub fn matches_stored_to(&self, stored: &[u8]) -> bool {
+        matches_stored(&self.to_nonce, &self.to_ciphertext_hash, stored)
+    }
+}
+
+fn matches_stored(nonce: &[u8], ciphertext_hash: &[u8; 32], stored: &[u8]) -> bool {
+    match stored.split_at_checked(nonce.len()) {
+        Some((stored_nonce, ciphertext)) => {
+            stored_nonce == nonce && crate::ciphertext_hash(ciphertext) == *ciphertext_hash
+        }
+        None => false,
+    }
+}
+
+/// Errors decoding a journal.
+#[derive(Debug)]
+pub enum JournalError {
+    /// The bytes are not a valid ABI encoding of the journal struct.
+    Abi(alloy_sol_types::Error),
+
+    /// The journal was produced by a guest with a different layout.
+    UnsupportedVersion(u8),
+
+    /// The draft identifier does not name a known JSON Schema draft.
+    UnknownDraft(u8),
+
+    /// The algorithm identifier does not name a supported AEAD.
+    UnknownAlgorithm(u8),
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalError::Abi(err) => write!(f, "invalid ABI journal: {err}"),
+            JournalError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported journal version {version}, expected {JOURNAL_VERSION}"
+            ),
+            JournalError::UnknownDraft(draft) => write!(f, "unknown schema draft id {draft}"),
+            JournalError::UnknownAlgorithm(algorithm) => {
+                write!(f, "unknown algorithm id {algorithm}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abi_round_trip() {
+        let mut journal = EncryptJournal {
+            version: JOURNAL_VERSION,
+            algorithm: Algorithm::Aes256Gcm,
+            nonce: vec![9; 12],
+            aad: b"addition data".to_vec(),
+            plaintext_hash: [1; 32],
+            ciphertext_hash: [2; 32],
+            key_commitment: [3; 32],
+            ciphertext: None,
+        };
+        assert_eq!(
+            EncryptJournal::abi_decode(&journal.abi_encode()).unwrap(),
+            journal
+        );
+        journal.ciphertext = Some(vec![4; 18]);
+        assert_eq!(
+            EncryptJournal::abi_decode(&journal.abi_encode()).unwrap(),
+            journal
+        );
+
+        let journal = DecryptJournal {
+            version: JOURNAL_VERSION,
+            algorithm: Algorithm::ChaCha20Poly1305,
+            nonce: vec![9; 12],
+            aad: vec![],
+            ciphertext_hash: [1; 32],
+            plaintext_hash: [2; 32],
+            key_commitment: [3; 32],
+        };
+        assert_eq!(
+            DecryptJournal::abi_decode(&journal.abi_encode()).unwrap(),
+            journal
+        );
+
+        let journal = SchemaEncryptJournal {
+            version: JOURNAL_VERSION,
+            algorithm: Algorithm::Aes128Gcm,
+            nonce: vec![9; 12],
+            ciphertext_hash: [1; 32],
+            key_commitment: [3; 32],
+            aad: b"addition data".to_vec(),
+            schema_hash: [2; 32],
+            draft: SchemaDraft::Draft7,
+        };
+        assert_eq!(
+            SchemaEncryptJournal::abi_decode(&journal.abi_encode()).unwrap(),
+            journal
+        );
+
+        let journal = StreamEncryptJournal {
+            version: JOURNAL_VERSION,
+            algorithm: Algorithm::ChaCha20Poly1305,
+            nonce_prefix: vec![9; 7],
+            aad: vec![],
+            chunk_len: 65536,
+            chunk_count: 3,
+            plaintext_hash: [1; 32],
+            ciphertext_chain: [2; 32],
+            key_commitment: [3; 32],
+        };
+        assert_eq!(
+            StreamEncryptJournal::abi_decode(&journal.abi_encode()).unwrap(),
+            journal
+        );
+
+        let journal = ReencryptJournal {
+            version: JOURNAL_VERSION,
+            aad: b"addition data".to_vec(),
+            from_algorithm: Algorithm::Aes128Gcm,
+            from_nonce: vec![9; 12],
+            from_ciphertext_hash: [1; 32],
+            from_key_commitment: [2; 32],
+            to_algorithm: Algorithm::Aes256Gcm,
+            to_nonce: vec![8; 12],
+            to_ciphertext_hash: [3; 32],
+            to_key_commitment: [4; 32],
+        };
+        assert_eq!(
+            ReencryptJournal::abi_decode(&journal.abi_encode()).unwrap(),
+            journal
+        );
+    }
+
+    #[test]
+    fn stored() {
+        let ciphertext = [5u8; 18];
+        let journal = EncryptJournal {
+            version: JOURNAL_VERSION,
+            algorithm: Algorithm::Aes256Gcm,
+            nonce: vec![9; 12],
+            aad: vec![],
+            plaintext_hash: [1; 32],
+            ciphertext_hash: crate::ciphertext_hash(&ciphertext),
+            key_commitment: [3; 32],
+            ciphertext: None,
+        };
+        let stored = crate::stored_ciphertext(&journal.nonce, &ciphertext);
+        assert!(journal.matches_stored(&stored));
+        assert!(!journal.matches_stored(&stored[1..]));
+        assert!(!journal.matches_stored(&ciphertext));
+    }
+
+    #[test]
+    fn stored_stream() {
+        for len in [0, 250, 300] {
+            let plaintext = vec![5u8; len];
+            let mut encryptor =
+                crate::StreamEncryptor::new(Algorithm::Aes256Gcm, vec![7; 32], vec![9; 7], vec![])
+                    .unwrap();
+            let mut stored = vec![9; 7];
+            encryptor
+                .encrypt_reader(&plaintext[..], 100, |chunk| {
+                    stored.extend_from_slice(chunk);
+                    Ok(())
+                })
+                .unwrap();
+            let journal = StreamEncryptJournal {
+                version: JOURNAL_VERSION,
+                algorithm: Algorithm::Aes256Gcm,
+                nonce_prefix: vec![9; 7],
+                aad: vec![],
+                chunk_len: 100,
+                chunk_count: encryptor.chunk_count(),
+                plaintext_hash: encryptor.plaintext_hash(),
+                ciphertext_chain: encryptor.chain(),
+                key_commitment: [3; 32],
+            };
+            assert!(journal.matches_stored(&stored));
+            assert!(!journal.matches_stored(&stored[..stored.len() - 1]));
+        }
+    }
+}
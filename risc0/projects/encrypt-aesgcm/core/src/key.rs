@@ -0,0 +1,179 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Key material and commitments to it.
+//!
+//! The host sends a [KeySource] to the guest, which derives the key itself, so that a
+//! passphrase is stretched inside the proof. Journals commit to the derived key with
+//! [key_commitment]: encrypting several documents with the same key and salt yields the same
+//! commitment, which shows they share a key without revealing it.
+
+use std::fmt;
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::Algorithm;
+
+/// Length of an AES-256 or ChaCha20-Poly1305 key in bytes. AES-128 keys are half as long.
+pub const KEY_LEN: usize = 32;
+
+/// Length of the salt in a [key_commitment].
+pub const KEY_SALT_LEN: usize = 32;
+
+/// Default PBKDF2 iteration count. Every iteration is executed in the guest, so this trades
+/// resistance to guessing against proving time.
+pub const PBKDF2_ROUNDS: u32 = 100_000;
+
+/// Where the guest gets its key from.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum KeySource {
+    /// The key itself, of [Algorithm::key_len] bytes.
+    Raw(Vec<u8>),
+
+    /// A key stretched from a passphrase with PBKDF2-HMAC-SHA256.
+    Pbkdf2 {
+        passphrase: String,
+        salt: Vec<u8>,
+        rounds: u32,
+    },
+
+    /// A key expanded with HKDF-SHA256. Only suitable for high-entropy input keying material,
+    /// not for passphrases.
+    Hkdf {
+        ikm: Vec<u8>,
+        salt: Vec<u8>,
+        info: Vec<u8>,
+    },
+}
+
+impl KeySource {
+    /// The key for `algorithm`.
+    ///
+    /// Derived keys have the algorithm's length. A raw key is returned as is, and the cipher
+    /// rejects it if its length is wrong.
+    pub fn derive(&self, algorithm: Algorithm) -> Vec<u8> {
+        let mut key = vec![0u8; algorithm.key_len()];
+        match self {
+            KeySource::Raw(raw) => return raw.clone(),
+            KeySource::Pbkdf2 {
+                passphrase,
+                salt,
+                rounds,
+            } => pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, *rounds, &mut key),
+            KeySource::Hkdf { ikm, salt, info } => Hkdf::<Sha256>::new(Some(salt), ikm)
+                .expand(info, &mut key)
+                .expect("16 and 32 bytes are valid HKDF-SHA256 output lengths"),
+        }
+        key
+    }
+}
+
+/// Commitment to a key: `sha256(key || salt)`.
+pub fn key_commitment(key: &[u8], salt: &[u8; KEY_SALT_LEN]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(key);
+    hasher.update(salt);
+    hasher.finalize().into()
+}
+
+/// Parse a 16- or 32-byte key encoded as hex digits, optionally prefixed with `0x`, or as
+/// standard base64.
+pub fn parse_key(encoded: &str) -> Result<Vec<u8>, KeyError> {
+    let encoded = encoded.trim();
+    let hex_key = encoded.strip_prefix("0x").unwrap_or(encoded);
+    let hex_bytes = match hex_key.len() {
+        32 | 64 => hex::decode(hex_key).ok(),
+        _ => None,
+    };
+    let bytes = match hex_bytes {
+        Some(bytes) => bytes,
+        None => BASE64_STANDARD
+            .decode(encoded)
+            .map_err(|_| KeyError::Encoding)?,
+    };
+    match bytes.len() {
+        16 | KEY_LEN => Ok(bytes),
+        len => Err(KeyError::Length(len)),
+    }
+}
+
+/// Errors parsing a key.
+#[derive(Debug)]
+pub enum KeyError {
+    /// The key is neither hex nor base64.
+    Encoding,
+
+    /// The key does not decode to 16 or [KEY_LEN] bytes.
+    Length(usize),
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::Encoding => write!(f, "expected a hex or base64 encoded key"),
+            KeyError::Length(len) => write!(f, "key is {len} bytes, expected 16 or {KEY_LEN}"),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derive() {
+        // PBKDF2-HMAC-SHA256 and RFC 5869 test case 1, truncated to 32 bytes.
+        let pbkdf2 = KeySource::Pbkdf2 {
+            passphrase: "password".into(),
+            salt: b"salt".to_vec(),
+            rounds: 1,
+        };
+        assert_eq!(
+            hex::encode(pbkdf2.derive(Algorithm::Aes256Gcm)),
+            "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b"
+        );
+        let hkdf = KeySource::Hkdf {
+            ikm: vec![0x0b; 22],
+            salt: (0x00..=0x0c).collect(),
+            info: (0xf0..=0xf9).collect(),
+        };
+        assert_eq!(
+            hex::encode(hkdf.derive(Algorithm::Aes256Gcm)),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf"
+        );
+        assert_eq!(
+            hex::encode(hkdf.derive(Algorithm::Aes128Gcm)),
+            "3cb25f25faacd57a90434f64d0362f2a"
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let key = [0xab; KEY_LEN];
+        assert_eq!(parse_key(&hex::encode(key)).unwrap(), key);
+        assert_eq!(parse_key(&format!("0x{}", hex::encode(key))).unwrap(), key);
+        assert_eq!(parse_key(&BASE64_STANDARD.encode(key)).unwrap(), key);
+        assert_eq!(parse_key(&hex::encode([0xab; 16])).unwrap(), [0xab; 16]);
+        assert!(matches!(
+            parse_key("thiskeystrmustbe32charlongtowork"),
+            Err(KeyError::Length(24))
+        ));
+        assert!(matches!(parse_key("not a key"), Err(KeyError::Encoding)));
+    }
+}
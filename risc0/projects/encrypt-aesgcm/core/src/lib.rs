@@ -12,10 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// use risc0_zkvm::sha::Digest;
-use serde::{Deserialize, Serialize};
+mod cipher;
+mod input;
+mod journal;
+mod key;
+mod stream;
 
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-pub struct Outputs {
-    pub result: u32,
-}
+pub use cipher::{
+    ciphertext_hash, decrypt, encrypt, plaintext_hash, stored_ciphertext, Algorithm, CipherError,
+    NONCE_LEN,
+};
+pub use input::{
+    DecryptInput, EncryptInput, ReencryptInput, SchemaEncryptInput, StreamEncryptInput,
+};
+pub use journal::{
+    DecryptJournal, EncryptJournal, JournalError, ReencryptJournal, SchemaEncryptJournal,
+    StreamEncryptJournal, JOURNAL_VERSION,
+};
+pub use key::{
+    key_commitment, parse_key, KeyError, KeySource, KEY_LEN, KEY_SALT_LEN, PBKDF2_ROUNDS,
+};
+pub use stream::{
+    chain_hash, stream_nonce, StreamEncryptor, StreamError, DEFAULT_CHUNK_LEN,
+    STREAM_NONCE_PREFIX_LEN,
+};
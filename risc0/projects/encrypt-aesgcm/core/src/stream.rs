@@ -0,0 +1,305 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Chunked encryption with the STREAM construction, for documents too large for guest memory.
+//!
+//! The plaintext is split into chunks of a fixed length, and chunk `i` is encrypted under the
+//! nonce `prefix || i as u32 big-endian || last`, where `last` is 1 for the final chunk and 0
+//! otherwise. This is the `StreamBE32` layout of the `aead` crate, so its `DecryptorBE32` can
+//! decrypt the result. Reordering, dropping or truncating chunks fails authentication.
+//!
+//! The encrypt_stream guest reads the plaintext from stdin one chunk at a time and commits a hash
+//! chain over the ciphertext chunks, see [chain_hash].
+
+use std::{fmt, io, io::Read};
+
+use sha2::{Digest, Sha256};
+
+use crate::{encrypt, Algorithm, CipherError, NONCE_LEN};
+
+/// Length of the nonce prefix; the remaining 5 bytes of the nonce are the counter and flag.
+pub const STREAM_NONCE_PREFIX_LEN: usize = NONCE_LEN - 5;
+
+/// Default plaintext chunk length in bytes.
+pub const DEFAULT_CHUNK_LEN: u32 = 64 * 1024;
+
+/// The nonce of chunk `counter`.
+pub fn stream_nonce(
+    prefix: &[u8],
+    counter: u32,
+    last: bool,
+) -> Result<[u8; NONCE_LEN], CipherError> {
+    if prefix.len() != STREAM_NONCE_PREFIX_LEN {
+        return Err(CipherError::NonceLength(prefix.len()));
+    }
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..STREAM_NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[STREAM_NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&counter.to_be_bytes());
+    nonce[NONCE_LEN - 1] = last as u8;
+    Ok(nonce)
+}
+
+/// Extend the ciphertext hash chain with a chunk: `sha256(chain || chunk)`.
+///
+/// The chain starts at 32 zero bytes. Its final value commits to every ciphertext chunk in
+/// order, tags included.
+pub fn chain_hash(chain: &[u8; 32], chunk: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(chain);
+    hasher.update(chunk);
+    hasher.finalize().into()
+}
+
+/// Encrypts a stream chunk by chunk, keeping the hash chain and the plaintext hash.
+#[derive(Clone)]
+pub struct StreamEncryptor {
+    algorithm: Algorithm,
+    key: Vec<u8>,
+    nonce_prefix: Vec<u8>,
+    aad: Vec<u8>,
+    chunk_count: u64,
+    finished: bool,
+    chain: [u8; 32],
+    plaintext: Sha256,
+}
+
+impl StreamEncryptor {
+    /// Start a stream. Every chunk authenticates `aad`.
+    pub fn new(
+        algorithm: Algorithm,
+        key: Vec<u8>,
+        nonce_prefix: Vec<u8>,
+        aad: Vec<u8>,
+    ) -> Result<Self, CipherError> {
+        if nonce_prefix.len() != STREAM_NONCE_PREFIX_LEN {
+            return Err(CipherError::NonceLength(nonce_prefix.len()));
+        }
+        if key.len() != algorithm.key_len() {
+            return Err(CipherError::KeyLength(key.len()));
+        }
+        Ok(StreamEncryptor {
+            algorithm,
+            key,
+            nonce_prefix,
+            aad,
+            chunk_count: 0,
+            finished: false,
+            chain: [0; 32],
+            plaintext: Sha256::new(),
+        })
+    }
+
+    /// Encrypt the next chunk. `last` must be set on the final chunk, and only on it.
+    pub fn encrypt_chunk(&mut self, chunk: &[u8], last: bool) -> Result<Vec<u8>, CipherError> {
+        if self.finished {
+            return Err(CipherError::StreamEnded);
+        }
+        let counter = u32::try_from(self.chunk_count).map_err(|_| CipherError::StreamEnded)?;
+        let nonce = stream_nonce(&self.nonce_prefix, counter, last)?;
+        let ciphertext = encrypt(self.algorithm, &self.key, &nonce, &self.aad, chunk)?;
+        self.plaintext.update(chunk);
+        self.chain = chain_hash(&self.chain, &ciphertext);
+        self.chunk_count += 1;
+        self.finished = last;
+        Ok(ciphertext)
+    }
+
+    /// Encrypt everything `reader` yields in chunks of `chunk_len` bytes, passing each
+    /// ciphertext chunk to `sink`, and finish the stream.
+    ///
+    /// Only two chunks are held in memory: the last flag needs one chunk of lookahead. An empty
+    /// input is a single empty final chunk.
+    pub fn encrypt_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        chunk_len: usize,
+        mut sink: impl FnMut(&[u8]) -> io::Result<()>,
+    ) -> Result<(), StreamError> {
+        if chunk_len == 0 {
+            return Err(StreamError::ChunkLength);
+        }
+        let mut chunk = vec![0u8; chunk_len];
+        let mut next = vec![0u8; chunk_len];
+        let mut len = read_chunk(&mut reader, &mut chunk)?;
+        loop {
+            let next_len = match len {
+                full if full == chunk_len => read_chunk(&mut reader, &mut next)?,
+                _ => 0,
+            };
+            let last = next_len == 0;
+            let ciphertext = self.encrypt_chunk(&chunk[..len], last)?;
+            sink(&ciphertext)?;
+            if last {
+                return Ok(());
+            }
+            std::mem::swap(&mut chunk, &mut next);
+            len = next_len;
+        }
+    }
+
+    /// Number of chunks encrypted so far.
+    pub fn chunk_count(&self) -> u64 {
+        self.chunk_count
+    }
+
+    /// Whether the final chunk has been encrypted.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The hash chain over the ciphertext chunks so far, see [chain_hash].
+    pub fn chain(&self) -> [u8; 32] {
+        self.chain
+    }
+
+    /// SHA-256 of the plaintext so far, equal to [plaintext_hash](crate::plaintext_hash) of the
+    /// whole document once the stream is finished.
+    pub fn plaintext_hash(&self) -> [u8; 32] {
+        self.plaintext.clone().finalize().into()
+    }
+}
+
+/// Read until `buf` is full or the reader is exhausted, returning the number of bytes read.
+fn read_chunk<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+/// Errors encrypting a stream.
+#[derive(Debug)]
+pub enum StreamError {
+    /// Reading the plaintext or writing the ciphertext failed.
+    Io(io::Error),
+
+    /// Encrypting a chunk failed.
+    Cipher(CipherError),
+
+    /// The chunk length is zero.
+    ChunkLength,
+}
+
+impl From<io::Error> for StreamError {
+    fn from(err: io::Error) -> Self {
+        StreamError::Io(err)
+    }
+}
+
+impl From<CipherError> for StreamError {
+    fn from(err: CipherError) -> Self {
+        StreamError::Cipher(err)
+    }
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(f, "{err}"),
+            StreamError::Cipher(err) => write!(f, "{err}"),
+            StreamError::ChunkLength => write!(f, "chunk length must not be zero"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+#[cfg(test)]
+mod tests {
+    use aes_gcm::{
+        aead::{stream::DecryptorBE32, KeyInit},
+        Aes256Gcm,
+    };
+
+    use super::*;
+
+    fn encrypt_all(plaintext: &[u8], chunk_len: usize) -> (StreamEncryptor, Vec<Vec<u8>>) {
+        let mut encryptor = StreamEncryptor::new(
+            Algorithm::Aes256Gcm,
+            vec![7; 32],
+            vec![1; 7],
+            b"aad".to_vec(),
+        )
+        .unwrap();
+        let mut chunks = Vec::new();
+        encryptor
+            .encrypt_reader(plaintext, chunk_len, |chunk| {
+                chunks.push(chunk.to_vec());
+                Ok(())
+            })
+            .unwrap();
+        (encryptor, chunks)
+    }
+
+    #[test]
+    fn chunks() {
+        let plaintext: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for (chunk_len, count) in [(100, 10), (300, 4), (1000, 1), (4096, 1)] {
+            let (encryptor, chunks) = encrypt_all(&plaintext, chunk_len);
+            assert!(encryptor.is_finished());
+            assert_eq!(encryptor.chunk_count(), count);
+            assert_eq!(chunks.len() as u64, count);
+            assert_eq!(
+                encryptor.plaintext_hash(),
+                crate::plaintext_hash(&plaintext)
+            );
+            let chain = chunks
+                .iter()
+                .fold([0; 32], |chain, c| chain_hash(&chain, c));
+            assert_eq!(encryptor.chain(), chain);
+
+            // Interoperates with the aead crate's STREAM implementation.
+            let cipher = Aes256Gcm::new_from_slice(&[7; 32]).unwrap();
+            let mut decryptor = DecryptorBE32::from_aead(cipher, (&[1u8; 7][..]).into());
+            let mut decrypted = Vec::new();
+            let (last, rest) = chunks.split_last().unwrap();
+            for chunk in rest {
+                let payload = aes_gcm::aead::Payload {
+                    msg: chunk,
+                    aad: b"aad",
+                };
+                decrypted.extend(decryptor.decrypt_next(payload).unwrap());
+            }
+            let payload = aes_gcm::aead::Payload {
+                msg: last,
+                aad: b"aad",
+            };
+            decrypted.extend(decryptor.decrypt_last(payload).unwrap());
+            assert_eq!(decrypted, plaintext);
+        }
+
+        let (encryptor, chunks) = encrypt_all(b"", 100);
+        assert_eq!(encryptor.chunk_count(), 1);
+        assert_eq!(chunks[0].len(), 16);
+    }
+
+    #[test]
+    fn ended() {
+        let (mut encryptor, _) = encrypt_all(b"{}", 100);
+        assert!(matches!(
+            encryptor.encrypt_chunk(b"more", true),
+            Err(CipherError::StreamEnded)
+        ));
+        assert!(matches!(
+            StreamEncryptor::new(Algorithm::Aes128Gcm, vec![7; 32], vec![1; 7], vec![]),
+            Err(CipherError::KeyLength(32))
+        ));
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use encrypt_aesgcm_core::{
+    ciphertext_hash, decrypt, key_commitment, plaintext_hash, DecryptInput, DecryptJournal,
+    JOURNAL_VERSION,
+};
+use risc0_zkvm::guest::env;
+
+// Decrypts a ciphertext and commits the hash of the plaintext, without revealing the key.
+fn main() {
+    let input: DecryptInput = env::read();
+
+    // Derive the key in the guest, so that a passphrase is stretched inside the proof.
+    let key = input.key.derive(input.algorithm);
+
+    // A wrong key, nonce or AAD fails the tag check, and gets no receipt.
+    let plaintext = decrypt(
+        input.algorithm,
+        &key,
+        &input.nonce,
+        &input.aad,
+        &input.ciphertext,
+    )
+    .unwrap_or_else(|err| panic!("{err}"));
+
+    let journal = DecryptJournal {
+        version: JOURNAL_VERSION,
+        algorithm: input.algorithm,
+        ciphertext_hash: ciphertext_hash(&input.ciphertext),
+        plaintext_hash: plaintext_hash(&plaintext),
+        key_commitment: key_commitment(&key, &input.key_salt),
+        nonce: input.nonce,
+        aad: input.aad,
+    };
+
+    env::commit_slice(&journal.abi_encode());
+}
@@ -0,0 +1,53 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use encrypt_aesgcm_core::{
+    key_commitment, StreamEncryptInput, StreamEncryptJournal, StreamEncryptor, JOURNAL_VERSION,
+};
+use risc0_zkvm::guest::env;
+
+// Encrypts a document read from stdin chunk by chunk, holding only two chunks in memory, and
+// commits a hash chain over the ciphertext chunks.
+fn main() {
+    let input: StreamEncryptInput = env::read();
+
+    // Derive the key in the guest, so that a passphrase is stretched inside the proof.
+    let key = input.key.derive(input.algorithm);
+    let key_commitment = key_commitment(&key, &input.key_salt);
+
+    let mut encryptor = StreamEncryptor::new(
+        input.algorithm,
+        key,
+        input.nonce_prefix.clone(),
+        input.aad.clone(),
+    )
+    .unwrap_or_else(|err| panic!("{err}"));
+    // Only the chain is committed; the host reproduces the chunks.
+    encryptor
+        .encrypt_reader(env::stdin(), input.chunk_len as usize, |_| Ok(()))
+        .unwrap_or_else(|err| panic!("{err}"));
+
+    let journal = StreamEncryptJournal {
+        version: JOURNAL_VERSION,
+        algorithm: input.algorithm,
+        nonce_prefix: input.nonce_prefix,
+        aad: input.aad,
+        chunk_len: input.chunk_len,
+        chunk_count: encryptor.chunk_count(),
+        plaintext_hash: encryptor.plaintext_hash(),
+        ciphertext_chain: encryptor.chain(),
+        key_commitment,
+    };
+
+    env::commit_slice(&journal.abi_encode());
+}
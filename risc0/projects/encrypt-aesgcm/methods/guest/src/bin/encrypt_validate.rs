@@ -0,0 +1,62 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use encrypt_aesgcm_core::{
+    ciphertext_hash, encrypt, key_commitment, SchemaEncryptInput, SchemaEncryptJournal,
+    JOURNAL_VERSION,
+};
+use json_validate_core::CompiledSchema;
+use risc0_zkvm::guest::env;
+
+// Encrypts a JSON document and proves that it conforms to a schema, without revealing it.
+fn main() {
+    let input: SchemaEncryptInput = env::read();
+
+    let data: serde_json::Value = serde_json::from_str(&input.plaintext).unwrap();
+
+    // Compile the schema
+    let compiled_schema =
+        CompiledSchema::parse(&input.schema, input.draft).unwrap_or_else(|err| panic!("{err}"));
+
+    // A non-conforming document gets no receipt.
+    let errors = compiled_schema.validate(&data);
+    assert!(
+        errors.is_empty(),
+        "document does not conform to the schema: {errors:?}"
+    );
+
+    // Derive the key in the guest, so that a passphrase is stretched inside the proof.
+    let key = input.key.derive(input.algorithm);
+
+    let ciphertext = encrypt(
+        input.algorithm,
+        &key,
+        &input.nonce,
+        &input.aad,
+        input.plaintext.as_bytes(),
+    )
+    .unwrap_or_else(|err| panic!("{err}"));
+
+    let journal = SchemaEncryptJournal {
+        version: JOURNAL_VERSION,
+        algorithm: input.algorithm,
+        nonce: input.nonce,
+        ciphertext_hash: ciphertext_hash(&ciphertext),
+        key_commitment: key_commitment(&key, &input.key_salt),
+        aad: input.aad,
+        schema_hash: compiled_schema.schema_hash(),
+        draft: compiled_schema.draft(),
+    };
+
+    env::commit_slice(&journal.abi_encode());
+}
@@ -0,0 +1,62 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use encrypt_aesgcm_core::{
+    ciphertext_hash, decrypt, encrypt, key_commitment, ReencryptInput, ReencryptJournal,
+    JOURNAL_VERSION,
+};
+use risc0_zkvm::guest::env;
+
+// Decrypts a ciphertext under the old key and re-encrypts the plaintext under the new one,
+// committing to both ciphertexts and both keys but not to the plaintext.
+fn main() {
+    let input: ReencryptInput = env::read();
+
+    // Derive both keys in the guest, so that passphrases are stretched inside the proof.
+    let from_key = input.from_key.derive(input.from_algorithm);
+    let to_key = input.to_key.derive(input.to_algorithm);
+
+    // A wrong old key, nonce or AAD fails the tag check, and gets no receipt.
+    let plaintext = decrypt(
+        input.from_algorithm,
+        &from_key,
+        &input.from_nonce,
+        &input.aad,
+        &input.ciphertext,
+    )
+    .unwrap_or_else(|err| panic!("{err}"));
+
+    let reencrypted = encrypt(
+        input.to_algorithm,
+        &to_key,
+        &input.to_nonce,
+        &input.aad,
+        &plaintext,
+    )
+    .unwrap_or_else(|err| panic!("{err}"));
+
+    let journal = ReencryptJournal {
+        version: JOURNAL_VERSION,
+        from_algorithm: input.from_algorithm,
+        from_ciphertext_hash: ciphertext_hash(&input.ciphertext),
+        from_key_commitment: key_commitment(&from_key, &input.from_key_salt),
+        from_nonce: input.from_nonce,
+        to_algorithm: input.to_algorithm,
+        to_ciphertext_hash: ciphertext_hash(&reencrypted),
+        to_key_commitment: key_commitment(&to_key, &input.to_key_salt),
+        to_nonce: input.to_nonce,
+        aad: input.aad,
+    };
+
+    env::commit_slice(&journal.abi_encode());
+}
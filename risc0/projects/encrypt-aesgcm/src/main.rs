@@ -11,212 +11,958 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-// use serde_json::json;
-// use jsonschema::{Draft, JSONSchema};
-// use json_validate_core::Outputs;
-// use alloy::sol_types::SolValue;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use anyhow::{ensure, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use encrypt_aesgcm_core::{
+    ciphertext_hash, decrypt, encrypt, key_commitment, parse_key, plaintext_hash,
+    stored_ciphertext, Algorithm, DecryptInput, DecryptJournal, EncryptInput, EncryptJournal,
+    KeySource, ReencryptInput, ReencryptJournal, SchemaEncryptInput, SchemaEncryptJournal,
+    StreamEncryptInput, StreamEncryptJournal, StreamEncryptor, DEFAULT_CHUNK_LEN, KEY_SALT_LEN,
+    NONCE_LEN, PBKDF2_ROUNDS, STREAM_NONCE_PREFIX_LEN,
+};
+use encrypt_aesgcm_methods::{
+    DECRYPT_ELF, DECRYPT_ID, ENCRYPT_AESGCM_ELF, ENCRYPT_AESGCM_ID, ENCRYPT_STREAM_ELF,
+    ENCRYPT_STREAM_ID, ENCRYPT_VALIDATE_ELF, ENCRYPT_VALIDATE_ID, REENCRYPT_ELF, REENCRYPT_ID,
+};
+use json_validate_core::SchemaDraft;
+use receipt_seal::encode_seal;
+use receipt_store::{Index, ReceiptStore};
+use risc0_zkvm::{
+    compute_image_id, default_prover, ExecutorEnv, ProverOpts, Receipt, VerifierContext,
+};
+
+const AAD: &str = "addition data";
+
+/// Proves AES-GCM encryption of JSON documents using the RISC Zero zkVM.
+#[derive(Parser)]
+#[command(about, version)]
+struct Cli {
+    /// Defaults to encrypting the sample document.
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Where to store the nonce followed by the ciphertext, which the journal commits to.
+    /// Defaults to `<OUT_DIR>/ciphertext.bin`.
+    #[arg(long, global = true)]
+    ciphertext_out: Option<PathBuf>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
 
-use aes_gcm::aead::{OsRng};
-use aes_gcm::{AeadCore, Aes256Gcm};
-use encrypt_aesgcm_methods::{ENCRYPT_AESGCM_ELF,ENCRYPT_AESGCM_ID};
-// use risc0_zkvm::{default_prover, ExecutorEnv};
-use risc0_zkvm::{compute_image_id,default_prover, ExecutorEnv, ProverOpts, VerifierContext, InnerReceipt, sha::Digestible};
-use std::fs::File;
-use std::io::Write;
-use anyhow::{Result, bail, Context};
-use alloy_sol_types::SolValue;
-// use std::env;
-// use std::time::Instant;
+#[derive(Args)]
+struct OutputArgs {
+    /// Directory the receipt is written to, e.g. as `receipt_decrypt_groth16.json`.
+    #[arg(long, global = true, default_value = "./res")]
+    out_dir: PathBuf,
 
-fn main() {
+    /// Also add the receipt to the receipt store in DIR, indexed by its image ID and the hashes
+    /// its journal commits to. Unlike the receipt file, stored receipts are never overwritten.
+    #[arg(long, global = true, value_name = "DIR")]
+    store: Option<PathBuf>,
+}
 
-    let data = include_str!("../res/data_complex_obj.json");
-    let aad  = "addition data".to_string();
-    let key = "thiskeystrmustbe32charlongtowork".to_string();
-    let nonce = Aes256Gcm::generate_nonce(&mut OsRng).to_vec();
+impl OutputArgs {
+    /// Writes the receipt to `<out_dir>/receipt_<name>_groth16.json`, and adds it to the
+    /// receipt store under `index` if one was given.
+    fn write_receipt(&self, receipt: &Receipt, name: &str, index: Index) -> Result<()> {
+        fs::create_dir_all(&self.out_dir)
+            .with_context(|| format!("creating {}", self.out_dir.display()))?;
+        let path = self.out_dir.join(format!("receipt_{name}_groth16.json"));
+        fs::write(&path, serde_json::to_string_pretty(receipt)?)
+            .with_context(|| format!("writing {}", path.display()))?;
+        println!("receipt written to {}", path.display());
+
+        if let Some(dir) = &self.store {
+            let entry = ReceiptStore::open(dir)?.put(receipt, index)?;
+            println!("stored receipt {}", entry.id);
+        }
+        Ok(())
+    }
+}
 
-    // let _ = benchmark_prove(data, schema);
-    let _ = encrypt_aesgcm(&key, &aad, data, nonce);
+#[derive(Subcommand)]
+enum Command {
+    /// Prove that a ciphertext is the encryption of a document.
+    Encrypt {
+        /// Document to encrypt. Defaults to res/data_complex_obj.json.
+        data: Option<PathBuf>,
+
+        /// Additional authenticated data, committed to the journal in the clear.
+        #[arg(long, default_value = AAD)]
+        aad: String,
+
+        /// Commit the ciphertext itself to the journal, not just its hash.
+        #[arg(long)]
+        commit_ciphertext: bool,
+
+        #[command(flatten)]
+        key: KeyArgs,
+    },
+
+    /// Prove that a ciphertext is the encryption of a document conforming to a schema.
+    EncryptValidate {
+        /// JSON document to encrypt.
+        data: PathBuf,
+
+        /// JSON schema the document must conform to.
+        schema: PathBuf,
+
+        /// Additional authenticated data, committed to the journal in the clear.
+        #[arg(long, default_value = AAD)]
+        aad: String,
+
+        /// Override the schema draft instead of reading the schema's `$schema` keyword.
+        #[arg(long)]
+        draft: Option<SchemaDraft>,
+
+        #[command(flatten)]
+        key: KeyArgs,
+    },
+
+    /// Prove the chunked encryption of a document too large for guest memory.
+    EncryptStream {
+        /// Document to encrypt, streamed to the guest through stdin.
+        data: PathBuf,
+
+        /// Additional authenticated data of every chunk, committed to the journal in the clear.
+        #[arg(long, default_value = AAD)]
+        aad: String,
+
+        /// Plaintext bytes per chunk. The guest holds two chunks in memory.
+        #[arg(
+            long,
+            default_value_t = DEFAULT_CHUNK_LEN,
+            value_parser = clap::value_parser!(u32).range(1..)
+        )]
+        chunk_len: u32,
+
+        #[command(flatten)]
+        key: KeyArgs,
+    },
+
+    /// Prove that a stored ciphertext decrypts to a document with the committed hash, without
+    /// revealing the key.
+    Decrypt {
+        /// The nonce followed by the ciphertext, as written to --ciphertext-out.
+        ciphertext: PathBuf,
+
+        /// Additional authenticated data the ciphertext was encrypted with.
+        #[arg(long, default_value = AAD)]
+        aad: String,
+
+        /// Write the decrypted document here.
+        #[arg(long)]
+        plaintext_out: Option<PathBuf>,
+
+        #[command(flatten)]
+        key: KeyArgs,
+    },
+
+    /// Prove that a new ciphertext under a new key holds the same document as a stored one,
+    /// for key rotation. Neither the document nor its hash is revealed.
+    Reencrypt {
+        /// The nonce followed by the ciphertext under the old key, as written to
+        /// --ciphertext-out. It must not be the --ciphertext-out of the new ciphertext.
+        ciphertext: PathBuf,
+
+        /// Additional authenticated data of both ciphertexts.
+        #[arg(long, default_value = AAD)]
+        aad: String,
+
+        /// The old key, which must be given.
+        #[command(flatten)]
+        key: KeyArgs,
+
+        #[command(flatten)]
+        to: NewKeyArgs,
+    },
 }
 
+/// The key to re-encrypt under.
+#[derive(Args)]
+struct NewKeyArgs {
+    /// The AEAD to re-encrypt with.
+    #[arg(long, default_value_t = Algorithm::Aes256Gcm)]
+    to_algorithm: Algorithm,
 
-fn encrypt_aesgcm(key: &str, aad: &str, data: &str, nonce: Vec<u8>) -> Result<()> {
+    /// The new key, as hex digits or base64. Without it a random key is generated and printed.
+    #[arg(long, value_parser = parse_key_arg, conflicts_with = "to_key_file")]
+    to_key: Option<::std::vec::Vec<u8>>,
 
-    println!("nonce {:?}", nonce);
-    println!("key {}", key);
-    println!("data {}", data);
+    /// File holding the raw bytes of the new key.
+    #[arg(long)]
+    to_key_file: Option<PathBuf>,
 
-    let input = (key, aad, data, nonce);
+    /// Salt for the commitment to the new key, as 64 hex digits.
+    #[arg(long, value_parser = parse_key_salt)]
+    to_key_salt: Option<[u8; KEY_SALT_LEN]>,
+}
 
+impl NewKeyArgs {
+    /// The new key as [KeyArgs], to share their key handling.
+    fn key_args(&self) -> KeyArgs {
+        let mut key = KeyArgs {
+            algorithm: self.to_algorithm,
+            key_salt: self.to_key_salt,
+            ..KeyArgs::default()
+        };
+        key.source.key = self.to_key.clone();
+        key.source.key_file = self.to_key_file.clone();
+        key
+    }
+}
 
-    let env = ExecutorEnv::builder()
-        .write(&input)
-        .unwrap()
-        .build()
-        .unwrap();
+/// The algorithm, where its key comes from, and the salt of the key commitment.
+///
+/// Without a key source a random key is generated. Generated keys and salts are printed, since
+/// they are needed to decrypt and to reproduce the key commitment.
+#[derive(Args)]
+struct KeyArgs {
+    /// The AEAD: aes-256-gcm, aes-128-gcm or chacha20-poly1305.
+    #[arg(long, default_value_t = Algorithm::Aes256Gcm)]
+    algorithm: Algorithm,
+
+    #[command(flatten)]
+    source: KeySourceArgs,
+
+    /// Salt for --passphrase or --hkdf-ikm, hex-encoded.
+    #[arg(long, value_parser = parse_hex)]
+    kdf_salt: Option<::std::vec::Vec<u8>>,
+
+    /// PBKDF2 iterations for --passphrase, all of which run in the guest.
+    #[arg(long, default_value_t = PBKDF2_ROUNDS)]
+    pbkdf2_rounds: u32,
+
+    /// HKDF info string for --hkdf-ikm.
+    #[arg(long, default_value = "")]
+    hkdf_info: String,
+
+    /// Salt for the key commitment sha256(key || salt), as 64 hex digits. Reuse it across
+    /// documents to show that they were encrypted under the same key.
+    #[arg(long, value_parser = parse_key_salt)]
+    key_salt: Option<[u8; KEY_SALT_LEN]>,
+}
 
-    // // Obtain the default prover.
-    let prover = default_prover();
+#[derive(Args)]
+#[group(multiple = false)]
+struct KeySourceArgs {
+    /// The key, as hex digits or base64. AES-128 keys are 16 bytes, the others 32.
+    #[arg(long, value_parser = parse_key_arg)]
+    key: Option<::std::vec::Vec<u8>>,
+
+    /// File holding the raw key bytes.
+    #[arg(long)]
+    key_file: Option<PathBuf>,
+
+    /// Derive the key from a passphrase with PBKDF2-HMAC-SHA256 in the guest.
+    #[arg(long)]
+    passphrase: Option<String>,
+
+    /// Derive the key from hex-encoded high-entropy keying material with HKDF-SHA256 in the
+    /// guest.
+    #[arg(long, value_parser = parse_hex)]
+    hkdf_ikm: Option<::std::vec::Vec<u8>>,
+}
 
-    // // Produce a receipt by proving the specified ELF binary.
-    // let receipt = prover.prove(env, CHECK_SCHEMA_ELF).unwrap().receipt;
+impl Default for KeyArgs {
+    fn default() -> Self {
+        KeyArgs {
+            algorithm: Algorithm::default(),
+            source: KeySourceArgs {
+                key: None,
+                key_file: None,
+                passphrase: None,
+                hkdf_ikm: None,
+            },
+            kdf_salt: None,
+            pbkdf2_rounds: PBKDF2_ROUNDS,
+            hkdf_info: String::new(),
+            key_salt: None,
+        }
+    }
+}
 
-    println!("start prove ");
-    let receipt = prover.prove_with_ctx(
-        env,
-        &VerifierContext::default(),
-        ENCRYPT_AESGCM_ELF,
-        &ProverOpts::groth16(),
-    )?
-    .receipt;
+impl KeyArgs {
+    /// The key source to send to the guest.
+    fn key_source(&self) -> Result<KeySource> {
+        let kdf_salt = || {
+            self.kdf_salt.clone().unwrap_or_else(|| {
+                let salt = random_bytes::<16>();
+                println!("kdf salt: {}", hex::encode(salt));
+                salt.to_vec()
+            })
+        };
+
+        let key_len = self.algorithm.key_len();
+        let source = &self.source;
+        Ok(if let Some(key) = &source.key {
+            ensure!(
+                key.len() == key_len,
+                "--key is {} bytes, {} needs {key_len}",
+                key.len(),
+                self.algorithm
+            );
+            KeySource::Raw(key.clone())
+        } else if let Some(path) = &source.key_file {
+            let key = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+            ensure!(
+                key.len() == key_len,
+                "{}: key is {} bytes, {} needs {key_len}",
+                path.display(),
+                key.len(),
+                self.algorithm
+            );
+            KeySource::Raw(key)
+        } else if let Some(passphrase) = &source.passphrase {
+            KeySource::Pbkdf2 {
+                passphrase: passphrase.clone(),
+                salt: kdf_salt(),
+                rounds: self.pbkdf2_rounds,
+            }
+        } else if let Some(ikm) = &source.hkdf_ikm {
+            KeySource::Hkdf {
+                ikm: ikm.clone(),
+                salt: kdf_salt(),
+                info: self.hkdf_info.clone().into_bytes(),
+            }
+        } else {
+            let mut key = vec![0u8; key_len];
+            OsRng.fill_bytes(&mut key);
+            println!("key: {}", hex::encode(&key));
+            KeySource::Raw(key)
+        })
+    }
 
-    println!("start verify ");
-    receipt.verify(ENCRYPT_AESGCM_ID).unwrap();
+    /// The key source for a key that must already exist, such as a decryption key.
+    fn existing_key_source(&self) -> Result<KeySource> {
+        let source = &self.source;
+        let derived = source.passphrase.is_some() || source.hkdf_ikm.is_some();
+        ensure!(
+            source.key.is_some() || source.key_file.is_some() || derived,
+            "a key is required: pass --key, --key-file, --passphrase or --hkdf-ikm"
+        );
+        ensure!(
+            !derived || self.kdf_salt.is_some(),
+            "--kdf-salt is required to derive an existing key"
+        );
+        self.key_source()
+    }
 
-    // Encode the seal with the selector.
-    let seal = encode_seal(&receipt)?;
+    /// The salt of the key commitment.
+    fn key_salt(&self) -> [u8; KEY_SALT_LEN] {
+        self.key_salt.unwrap_or_else(|| {
+            let salt = random_bytes();
+            println!("key salt: {}", hex::encode(salt));
+            salt
+        })
+    }
+}
 
-    // let seal_hex_string = vec_to_hex_string(&seal);
-    println!("seal hex_string: {}", hex::encode(seal));
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
 
-        
-    // Extract the journal from the receipt.
-    let journal = receipt.journal.bytes.clone();
+fn parse_key_arg(key: &str) -> Result<Vec<u8>, String> {
+    parse_key(key).map_err(|err| err.to_string())
+}
 
-    // Decode Journal: Upon receiving the proof, the application decodes the journal to extract
-    // the verified number. This ensures that the number being submitted to the blockchain matches
-    // the number that was verified off-chain.
+fn parse_key_salt(salt: &str) -> Result<[u8; KEY_SALT_LEN], String> {
+    let mut bytes = [0u8; KEY_SALT_LEN];
+    hex::decode_to_slice(salt.trim_start_matches("0x"), &mut bytes)
+        .map_err(|err| format!("expected {KEY_SALT_LEN} hex-encoded bytes: {err}"))?;
+    Ok(bytes)
+}
 
+fn parse_hex(bytes: &str) -> Result<Vec<u8>, String> {
+    hex::decode(bytes.trim_start_matches("0x")).map_err(|err| format!("invalid hex: {err}"))
+}
 
-    println!("journal: {}", hex::encode(journal.clone()));
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let nonce = random_bytes::<NONCE_LEN>().to_vec();
+    let output = &cli.output;
+    let ciphertext_out = match cli.ciphertext_out {
+        Some(path) => path,
+        None => {
+            fs::create_dir_all(&output.out_dir)
+                .with_context(|| format!("creating {}", output.out_dir.display()))?;
+            output.out_dir.join("ciphertext.bin")
+        }
+    };
 
-    let x = Vec::<u8>::abi_decode(&journal, true).context("decoding journal data")?;
-    
-    println!("journal abi_decode: {}", hex::encode(x));
+    let command = cli.command.unwrap_or(Command::Encrypt {
+        data: None,
+        aad: AAD.to_string(),
+        commit_ciphertext: false,
+        key: KeyArgs::default(),
+    });
+    match command {
+        Command::Encrypt {
+            data,
+            aad,
+            commit_ciphertext,
+            key,
+        } => {
+            let data = match data {
+                Some(path) => fs::read_to_string(&path)
+                    .with_context(|| format!("reading {}", path.display()))?,
+                None => include_str!("../res/data_complex_obj.json").to_string(),
+            };
+            let ciphertext = encrypt_aesgcm(
+                output,
+                EncryptInput {
+                    algorithm: key.algorithm,
+                    key: key.key_source()?,
+                    key_salt: key.key_salt(),
+                    aad: aad.into_bytes(),
+                    plaintext: data,
+                    nonce: nonce.clone(),
+                    commit_ciphertext,
+                },
+            )?;
+            store_ciphertext(&ciphertext_out, &nonce, &ciphertext)
+        }
+        Command::EncryptValidate {
+            data,
+            schema,
+            aad,
+            draft,
+            key,
+        } => {
+            let ciphertext = encrypt_validate(
+                output,
+                SchemaEncryptInput {
+                    algorithm: key.algorithm,
+                    key: key.key_source()?,
+                    key_salt: key.key_salt(),
+                    nonce: nonce.clone(),
+                    aad: aad.into_bytes(),
+                    plaintext: fs::read_to_string(&data)
+                        .with_context(|| format!("reading {}", data.display()))?,
+                    schema: fs::read_to_string(&schema)
+                        .with_context(|| format!("reading {}", schema.display()))?,
+                    draft,
+                },
+            )?;
+            store_ciphertext(&ciphertext_out, &nonce, &ciphertext)
+        }
+        Command::EncryptStream {
+            data,
+            aad,
+            chunk_len,
+            key,
+        } => encrypt_stream(
+            &data,
+            StreamEncryptInput {
+                algorithm: key.algorithm,
+                key: key.key_source()?,
+                key_salt: key.key_salt(),
+                nonce_prefix: nonce[..STREAM_NONCE_PREFIX_LEN].to_vec(),
+                aad: aad.into_bytes(),
+                chunk_len,
+            },
+            &ciphertext_out,
+            output,
+        ),
+        Command::Decrypt {
+            ciphertext,
+            aad,
+            plaintext_out,
+            key,
+        } => {
+            let (nonce, ciphertext) = read_stored(&ciphertext)?;
+            let plaintext = decrypt_aesgcm(
+                output,
+                DecryptInput {
+                    algorithm: key.algorithm,
+                    key: key.existing_key_source()?,
+                    key_salt: key.key_salt(),
+                    nonce,
+                    aad: aad.into_bytes(),
+                    ciphertext,
+                },
+            )?;
+            if let Some(path) = plaintext_out {
+                fs::write(&path, plaintext)
+                    .with_context(|| format!("writing {}", path.display()))?;
+            }
+            Ok(())
+        }
+        Command::Reencrypt {
+            ciphertext,
+            aad,
+            key,
+            to,
+        } => {
+            ensure!(
+                !same_file(&ciphertext, &ciphertext_out),
+                "reencrypt would overwrite the old ciphertext {}; pass another --ciphertext-out",
+                ciphertext.display()
+            );
+            let (from_nonce, ciphertext) = read_stored(&ciphertext)?;
+            let from_key = key.existing_key_source()?;
+            let from_key_salt = key.key_salt();
+            // Generated values printed from here on belong to the new key.
+            println!("new key:");
+            let to = to.key_args();
+            let reencrypted = reencrypt_aesgcm(
+                output,
+                ReencryptInput {
+                    from_algorithm: key.algorithm,
+                    from_key,
+                    from_key_salt,
+                    from_nonce,
+                    ciphertext,
+                    aad: aad.into_bytes(),
+                    to_algorithm: to.algorithm,
+                    to_key: to.key_source()?,
+                    to_key_salt: to.key_salt(),
+                    to_nonce: nonce.clone(),
+                },
+            )?;
+            store_ciphertext(&ciphertext_out, &nonce, &reencrypted)
+        }
+    }
+}
 
-    // Compute the Image ID
-    let image_id = hex::encode(compute_image_id(ENCRYPT_AESGCM_ELF)?);
+/// Whether both paths name the same existing file.
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
 
-    println!("Image ID: {}", image_id);
+/// Reads a ciphertext stored by [store_ciphertext], returning the nonce and the ciphertext.
+fn read_stored(path: &Path) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut ciphertext = fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    ensure!(
+        ciphertext.len() >= NONCE_LEN,
+        "{} is too short to start with a nonce",
+        path.display()
+    );
+    let nonce = ciphertext.drain(..NONCE_LEN).collect();
+    Ok((nonce, ciphertext))
+}
+
+/// Stores the nonce with the ciphertext; the journal commits to both.
+fn store_ciphertext(path: &Path, nonce: &[u8], ciphertext: &[u8]) -> Result<()> {
+    let stored = stored_ciphertext(nonce, ciphertext);
+    println!("ciphertext: {}", hex::encode(&stored));
+    fs::write(path, &stored).with_context(|| format!("writing {}", path.display()))
+}
 
-    // Dump receipe using serde
-    let receipt_json = serde_json::to_string_pretty(&receipt).unwrap();
+/// Proves that the re-encrypted ciphertext holds the same document as `input.ciphertext`, and
+/// returns it.
+fn reencrypt_aesgcm(output: &OutputArgs, input: ReencryptInput) -> Result<Vec<u8>> {
+    let env = ExecutorEnv::builder().write(&input)?.build()?;
 
-    // Write the JSON string to a file
-    let mut file = File::create("./res/receipt_aesgcm_groth16.json").expect("failed to create file");
-    file.write_all(receipt_json.as_bytes()).expect("failed to write");
+    let receipt = default_prover()
+        .prove_with_ctx(
+            env,
+            &VerifierContext::default(),
+            REENCRYPT_ELF,
+            &ProverOpts::groth16(),
+        )
+        .context("proving failed; are the old key, nonce and AAD right?")?
+        .receipt;
+    receipt.verify(REENCRYPT_ID)?;
+    let journal = ReencryptJournal::abi_decode(&receipt.journal.bytes)?;
+
+    // Reproduce the new ciphertext, which the journal only commits to by hash.
+    let from_key = input.from_key.derive(input.from_algorithm);
+    let to_key = input.to_key.derive(input.to_algorithm);
+    let plaintext = decrypt(
+        input.from_algorithm,
+        &from_key,
+        &input.from_nonce,
+        &input.aad,
+        &input.ciphertext,
+    )?;
+    let reencrypted = encrypt(
+        input.to_algorithm,
+        &to_key,
+        &input.to_nonce,
+        &input.aad,
+        &plaintext,
+    )?;
+    ensure!(
+        ciphertext_hash(&reencrypted) == journal.to_ciphertext_hash,
+        "the journal commits to a different new ciphertext"
+    );
+
+    println!("from algorithm: {}", journal.from_algorithm);
+    println!(
+        "from ciphertext hash: {}",
+        hex::encode(journal.from_ciphertext_hash)
+    );
+    println!(
+        "from key commitment: {}",
+        hex::encode(journal.from_key_commitment)
+    );
+    println!("to algorithm: {}", journal.to_algorithm);
+    println!(
+        "to ciphertext hash: {}",
+        hex::encode(journal.to_ciphertext_hash)
+    );
+    println!(
+        "to key commitment: {}",
+        hex::encode(journal.to_key_commitment)
+    );
+    println!("aad: {}", String::from_utf8_lossy(&journal.aad));
+    println!("seal: {}", hex::encode(encode_seal(&receipt.inner)?));
+    println!(
+        "Image ID: {}",
+        hex::encode(compute_image_id(REENCRYPT_ELF)?)
+    );
+
+    let index = Index::new(REENCRYPT_ID, "reencrypt");
+    output.write_receipt(&receipt, "reencrypt", index)?;
+    Ok(reencrypted)
+}
 
-    // println!("Data written to file successfully.");
+/// Proves the chunked encryption of the document at `data`, and stores the nonce prefix
+/// followed by the ciphertext chunks at `out`.
+fn encrypt_stream(
+    data: &Path,
+    input: StreamEncryptInput,
+    out: &Path,
+    output: &OutputArgs,
+) -> Result<()> {
+    let open = || File::open(data).with_context(|| format!("opening {}", data.display()));
+    let env = ExecutorEnv::builder()
+        .write(&input)?
+        .stdin(open()?)
+        .build()?;
 
-    // receipt.journal.decode.unwrap();
+    let receipt = default_prover()
+        .prove_with_ctx(
+            env,
+            &VerifierContext::default(),
+            ENCRYPT_STREAM_ELF,
+            &ProverOpts::groth16(),
+        )?
+        .receipt;
+    receipt.verify(ENCRYPT_STREAM_ID)?;
+    let journal = StreamEncryptJournal::abi_decode(&receipt.journal.bytes)?;
+
+    // Reproduce the chunks the guest chained, writing them out as they are encrypted.
+    let mut encryptor = StreamEncryptor::new(
+        input.algorithm,
+        input.key.derive(input.algorithm),
+        input.nonce_prefix.clone(),
+        input.aad,
+    )?;
+    let mut writer =
+        BufWriter::new(File::create(out).with_context(|| format!("creating {}", out.display()))?);
+    writer.write_all(&input.nonce_prefix)?;
+    encryptor.encrypt_reader(open()?, input.chunk_len as usize, |chunk| {
+        writer.write_all(chunk)
+    })?;
+    writer.flush()?;
+    ensure!(
+        encryptor.chain() == journal.ciphertext_chain
+            && encryptor.chunk_count() == journal.chunk_count,
+        "the journal commits to different ciphertext chunks"
+    );
+
+    println!("algorithm: {}", journal.algorithm);
+    println!(
+        "chunks: {} of {} bytes",
+        journal.chunk_count, journal.chunk_len
+    );
+    println!("plaintext hash: {}", hex::encode(journal.plaintext_hash));
+    println!(
+        "ciphertext chain: {}",
+        hex::encode(journal.ciphertext_chain)
+    );
+    println!("key commitment: {}", hex::encode(journal.key_commitment));
+    println!("ciphertext: {}", out.display());
+    println!("seal: {}", hex::encode(encode_seal(&receipt.inner)?));
+    println!(
+        "Image ID: {}",
+        hex::encode(compute_image_id(ENCRYPT_STREAM_ELF)?)
+    );
+
+    let index = Index {
+        data_hash: Some(journal.plaintext_hash),
+        ..Index::new(ENCRYPT_STREAM_ID, "encrypt_stream")
+    };
+    output.write_receipt(&receipt, "stream", index)?;
     Ok(())
 }
 
-// fn vec_to_hex_string(vec: &[u8]) -> String { 
-//     let mut hex_string = String::from("0x"); 
-//     for byte in vec { 
-//         hex_string.push_str(&format!("{:02x}", byte)); 
-//     } 
-//     hex_string 
-// }
-
-pub fn encode_seal(receipt: &risc0_zkvm::Receipt) -> Result<Vec<u8>> {
-    let seal = match receipt.inner.clone() {
-        InnerReceipt::Fake(receipt) => {
-            let seal = receipt.claim.digest().as_bytes().to_vec();
-            let selector = &[0u8; 4];
-            // Create a new vector with the capacity to hold both selector and seal
-            let mut selector_seal = Vec::with_capacity(selector.len() + seal.len());
-            selector_seal.extend_from_slice(selector);
-            selector_seal.extend_from_slice(&seal);
-            selector_seal
-        }
-        InnerReceipt::Groth16(receipt) => {
-            let selector = &receipt.verifier_parameters.as_bytes()[..4];
-            // Create a new vector with the capacity to hold both selector and seal
-            let mut selector_seal = Vec::with_capacity(selector.len() + receipt.seal.len());
-            selector_seal.extend_from_slice(selector);
-            selector_seal.extend_from_slice(receipt.seal.as_ref());
-            selector_seal
-        }
-        _ => bail!("Unsupported receipt type"),
+/// Proves that `input.ciphertext` decrypts to a document with the committed hash, and returns
+/// the document.
+fn decrypt_aesgcm(output: &OutputArgs, input: DecryptInput) -> Result<Vec<u8>> {
+    let env = ExecutorEnv::builder().write(&input)?.build()?;
+
+    let receipt = default_prover()
+        .prove_with_ctx(
+            env,
+            &VerifierContext::default(),
+            DECRYPT_ELF,
+            &ProverOpts::groth16(),
+        )
+        .context("proving failed; are the key, nonce and AAD right?")?
+        .receipt;
+    receipt.verify(DECRYPT_ID)?;
+    let journal = DecryptJournal::abi_decode(&receipt.journal.bytes)?;
+
+    let key = input.key.derive(input.algorithm);
+    let plaintext = decrypt(
+        input.algorithm,
+        &key,
+        &input.nonce,
+        &input.aad,
+        &input.ciphertext,
+    )?;
+    ensure!(
+        plaintext_hash(&plaintext) == journal.plaintext_hash,
+        "the journal commits to a different plaintext"
+    );
+
+    println!("algorithm: {}", journal.algorithm);
+    println!("ciphertext hash: {}", hex::encode(journal.ciphertext_hash));
+    println!("plaintext hash: {}", hex::encode(journal.plaintext_hash));
+    println!("key commitment: {}", hex::encode(journal.key_commitment));
+    println!("aad: {}", String::from_utf8_lossy(&journal.aad));
+    println!("seal: {}", hex::encode(encode_seal(&receipt.inner)?));
+    println!("Image ID: {}", hex::encode(compute_image_id(DECRYPT_ELF)?));
+
+    let index = Index {
+        data_hash: Some(journal.plaintext_hash),
+        ..Index::new(DECRYPT_ID, "decrypt")
     };
-    Ok(seal)
-}
-
-/*
-fn benchmark_prove(data: &str, schema: &str) ->Result<()>{
-    // start benchmarks
-    const ITER: usize = 1;
-    let mut benches = Vec::new();
-    let mut benches_verify = Vec::new();
-
-    let input = (data, schema);
-    for _ in 0..ITER {
-        // Obtain the default prover.
-        let prover = default_prover();
-
-        let env = ExecutorEnv::builder()
-            .write(&input)
-            .unwrap()
-            .build()
-            .unwrap();
-
-        let before = Instant::now();
-        // Produce a receipt by proving the specified ELF binary.
-        // let receipt  = prover.prove(env, CHECK_SCHEMA_ELF).unwrap().receipt;
-        let receipt = prover.prove_with_ctx(
+    output.write_receipt(&receipt, "decrypt", index)?;
+    Ok(plaintext)
+}
+
+/// Proves that the ciphertext of `input.plaintext` encrypts a document conforming to
+/// `input.schema`, and returns the ciphertext the receipt commits to.
+fn encrypt_validate(output: &OutputArgs, input: SchemaEncryptInput) -> Result<Vec<u8>> {
+    let env = ExecutorEnv::builder().write(&input)?.build()?;
+
+    let receipt = default_prover()
+        .prove_with_ctx(
             env,
             &VerifierContext::default(),
-            CHECK_SCHEMA_ELF,
+            ENCRYPT_VALIDATE_ELF,
+            &ProverOpts::groth16(),
+        )
+        .context("proving failed; does the document conform to the schema?")?
+        .receipt;
+    receipt.verify(ENCRYPT_VALIDATE_ID)?;
+    let journal = SchemaEncryptJournal::abi_decode(&receipt.journal.bytes)?;
+
+    // The guest does not reveal the ciphertext, but encryption is deterministic given the
+    // key and nonce, so the host reproduces it and checks it against the commitment.
+    let key = input.key.derive(input.algorithm);
+    let ciphertext = encrypt(
+        input.algorithm,
+        &key,
+        &input.nonce,
+        &input.aad,
+        input.plaintext.as_bytes(),
+    )?;
+    ensure!(
+        ciphertext_hash(&ciphertext) == journal.ciphertext_hash,
+        "the journal commits to a different ciphertext"
+    );
+    ensure!(
+        key_commitment(&key, &input.key_salt) == journal.key_commitment,
+        "the journal commits to a different key"
+    );
+
+    println!("algorithm: {}", journal.algorithm);
+    println!("ciphertext hash: {}", hex::encode(journal.ciphertext_hash));
+    println!("key commitment: {}", hex::encode(journal.key_commitment));
+    println!("aad: {}", String::from_utf8_lossy(&journal.aad));
+    println!("schema hash: {}", hex::encode(journal.schema_hash));
+    println!("draft: {}", journal.draft);
+    println!("seal: {}", hex::encode(encode_seal(&receipt.inner)?));
+    println!(
+        "Image ID: {}",
+        hex::encode(compute_image_id(ENCRYPT_VALIDATE_ELF)?)
+    );
+
+    let index = Index {
+        schema_hash: Some(journal.schema_hash),
+        ..Index::new(ENCRYPT_VALIDATE_ID, "encrypt_validate")
+    };
+    output.write_receipt(&receipt, "encrypt_validate", index)?;
+    Ok(ciphertext)
+}
+
+/// Proves the encryption of `input.plaintext` and returns the ciphertext the receipt commits to.
+fn encrypt_aesgcm(output: &OutputArgs, input: EncryptInput) -> Result<Vec<u8>> {
+    let env = ExecutorEnv::builder().write(&input)?.build()?;
+
+    let receipt = default_prover()
+        .prove_with_ctx(
+            env,
+            &VerifierContext::default(),
+            ENCRYPT_AESGCM_ELF,
             &ProverOpts::groth16(),
         )?
         .receipt;
-        // println!("\n###### Time: {:.2?}", before.elapsed());
-        benches.push(before.elapsed());
+    receipt.verify(ENCRYPT_AESGCM_ID)?;
+    let journal =
+        EncryptJournal::abi_decode(&receipt.journal.bytes).context("decoding journal data")?;
+
+    // Without --commit-ciphertext the guest only commits its hash, but encryption is
+    // deterministic given the key and nonce, so the host reproduces it.
+    let ciphertext = match journal.ciphertext {
+        Some(ciphertext) => ciphertext,
+        None => {
+            let key = input.key.derive(input.algorithm);
+            let plaintext = input.plaintext.as_bytes();
+            encrypt(input.algorithm, &key, &input.nonce, &input.aad, plaintext)?
+        }
+    };
+    ensure!(
+        ciphertext_hash(&ciphertext) == journal.ciphertext_hash,
+        "the journal commits to a different ciphertext"
+    );
+
+    println!("algorithm: {}", journal.algorithm);
+    println!("plaintext hash: {}", hex::encode(journal.plaintext_hash));
+    println!("ciphertext hash: {}", hex::encode(journal.ciphertext_hash));
+    println!("key commitment: {}", hex::encode(journal.key_commitment));
+    println!("aad: {}", String::from_utf8_lossy(&journal.aad));
+    println!("journal: {}", hex::encode(&receipt.journal.bytes));
+    println!("seal: {}", hex::encode(encode_seal(&receipt.inner)?));
+    println!(
+        "Image ID: {}",
+        hex::encode(compute_image_id(ENCRYPT_AESGCM_ELF)?)
+    );
+
+    let index = Index {
+        data_hash: Some(journal.plaintext_hash),
+        ..Index::new(ENCRYPT_AESGCM_ID, "encrypt_aesgcm")
+    };
+    output.write_receipt(&receipt, "aesgcm", index)?;
+    Ok(ciphertext)
+}
 
-        let before_verify = Instant::now();
-        receipt.verify(CHECK_SCHEMA_ID).unwrap();
-        benches_verify.push(before_verify.elapsed());
+#[cfg(test)]
+mod tests {
+    use receipt_store::Query;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const KEY: [u8; 32] = [7; 32];
+    const NEW_KEY: [u8; 32] = [9; 32];
+
+    /// Outputs in a temporary directory, with a receipt store. Receipts are fake, so proving
+    /// only executes the guest.
+    fn output() -> (OutputArgs, TempDir) {
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        let dir = tempfile::tempdir().unwrap();
+        let output = OutputArgs {
+            out_dir: dir.path().join("out"),
+            store: Some(dir.path().join("store")),
+        };
+        (output, dir)
     }
 
-    println!("\n-------- BENCHMARK VERIFY ---------");
-    for bench in benches_verify {
-        println!("{:.2?}", bench);
+    fn encrypt_input(plaintext: &str) -> EncryptInput {
+        EncryptInput {
+            algorithm: Algorithm::Aes256Gcm,
+            key: KeySource::Raw(KEY.to_vec()),
+            key_salt: [1; KEY_SALT_LEN],
+            aad: b"record 42".to_vec(),
+            plaintext: plaintext.to_string(),
+            nonce: vec![3; NONCE_LEN],
+            commit_ciphertext: false,
+        }
     }
-    println!("\n---------------------------");
-    
 
-    println!("\n-------- BENCHMARK ---------");
-    for bench in benches {
-        println!("{:.2?}", bench);
+    fn decrypt_input(key: &[u8], nonce: &[u8], ciphertext: Vec<u8>) -> DecryptInput {
+        DecryptInput {
+            algorithm: Algorithm::Aes256Gcm,
+            key: KeySource::Raw(key.to_vec()),
+            key_salt: [1; KEY_SALT_LEN],
+            nonce: nonce.to_vec(),
+            aad: b"record 42".to_vec(),
+            ciphertext,
+        }
     }
-    println!("\n---------------------------");
-    // end benchmarks
 
-    Ok(())
-}
-*/
-#[cfg(test)]
-mod tests {
-    use crate::check_schema;
     #[test]
-    fn success_case() {
+    fn encrypt_and_decrypt() {
+        let (output, _dir) = output();
         let data = include_str!("../res/data.json");
-        let schema = include_str!("../res/schema.json");
+        let input = encrypt_input(data);
+        let nonce = input.nonce.clone();
+        let ciphertext = encrypt_aesgcm(&output, input).unwrap();
+        assert!(output.out_dir.join("receipt_aesgcm_groth16.json").exists());
+
+        let plaintext = decrypt_aesgcm(&output, decrypt_input(&KEY, &nonce, ciphertext)).unwrap();
+        assert_eq!(plaintext, data.as_bytes());
+
+        let store = ReceiptStore::open(output.store.as_ref().unwrap()).unwrap();
+        let query = Query {
+            data_hash: Some(plaintext_hash(data.as_bytes())),
+            ..Default::default()
+        };
+        assert_eq!(store.list(&query).unwrap().len(), 2);
+    }
 
-        let outputs = check_schema(data, schema);
-        assert_eq!(
-            outputs.result, 1,
-            "The input data is not satisfy the schema"
-        );
+    #[test]
+    fn decrypt_with_wrong_key() {
+        let (output, _dir) = output();
+        let input = encrypt_input("{}");
+        let nonce = input.nonce.clone();
+        let ciphertext = encrypt_aesgcm(&output, input).unwrap();
+        assert!(decrypt_aesgcm(&output, decrypt_input(&NEW_KEY, &nonce, ciphertext)).is_err());
     }
+
     #[test]
-    fn fail_case() {
-        let data = include_str!("../res/data_failcase.json");
-        let schema = include_str!("../res/schema.json");
+    fn reencrypt() {
+        let (output, _dir) = output();
+        let data = include_str!("../res/data.json").as_bytes();
+        let from_nonce = vec![3; NONCE_LEN];
+        let ciphertext =
+            encrypt(Algorithm::Aes256Gcm, &KEY, &from_nonce, b"record 42", data).unwrap();
+
+        let to_nonce = vec![5; NONCE_LEN];
+        let reencrypted = reencrypt_aesgcm(
+            &output,
+            ReencryptInput {
+                from_algorithm: Algorithm::Aes256Gcm,
+                from_key: KeySource::Raw(KEY.to_vec()),
+                from_key_salt: [1; KEY_SALT_LEN],
+                from_nonce,
+                ciphertext,
+                aad: b"record 42".to_vec(),
+                to_algorithm: Algorithm::ChaCha20Poly1305,
+                to_key: KeySource::Raw(NEW_KEY.to_vec()),
+                to_key_salt: [2; KEY_SALT_LEN],
+                to_nonce: to_nonce.clone(),
+            },
+        )
+        .unwrap();
 
-        let outputs = check_schema(data, schema);
-        assert_eq!(outputs.result, 0, "The input data is satisfy the schema");
+        let plaintext = decrypt(
+            Algorithm::ChaCha20Poly1305,
+            &NEW_KEY,
+            &to_nonce,
+            b"record 42",
+            &reencrypted,
+        )
+        .unwrap();
+        assert_eq!(plaintext, data);
     }
 }
@@ -11,6 +11,7 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use json_validate_core::SchemaInput;
 use json_validate_methods::CHECK_SCHEMA_ELF;
 use risc0_zkvm::{default_prover, ExecutorEnv};
 use bencher::{benchmark_main, benchmark_group, Bencher};
@@ -19,7 +20,11 @@ fn bench_prove(b: &mut Bencher) {
     let data = include_str!("../res/data.json");
     let schema = include_str!("../res/schema.json");
 
-    let input = (data, schema);
+    let input = SchemaInput {
+        data: data.to_string(),
+        schema: schema.to_string(),
+        ..Default::default()
+    };
     
     // Obtain the default prover.
     let prover = default_prover();
@@ -0,0 +1,327 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Batch validation of many documents against one schema.
+//!
+//! The check_schema_batch guest commits a validity bitmap and a Merkle root over one leaf per
+//! document. A leaf binds the document's [record_commitment] to its validity bit, so an
+//! [InclusionProof] shows both that a record was covered by the batch receipt and whether it
+//! conformed.
+
+use alloy_sol_types::{sol, SolValue};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{data_commitment, raw_commitment, JournalError, SchemaDraft};
+
+/// Version of the journal layout committed by the check_schema_batch guest.
+///
+/// Bump this whenever a field of [BatchJournal] or the leaf encoding changes.
+pub const BATCH_JOURNAL_VERSION: u8 = 2;
+
+// Domain separation between leaves and interior nodes, so a node can never be passed off as a
+// leaf (or vice versa).
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+sol! {
+    struct AbiBatchJournal {
+        uint8 version;
+        bytes32 schemaHash;
+        uint8 draft;
+        uint32 count;
+        bytes validity;
+        bytes32 root;
+    }
+}
+
+/// The journal committed by the check_schema_batch guest.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BatchJournal {
+    /// Layout version, always [BATCH_JOURNAL_VERSION] for journals produced by this crate.
+    pub version: u8,
+
+    /// Commitment to the schema, see [schema_commitment](crate::schema_commitment).
+    pub schema_hash: [u8; 32],
+
+    /// The draft the schema was compiled against.
+    pub draft: SchemaDraft,
+
+    /// Number of documents in the batch.
+    pub count: u32,
+
+    /// One bit per document, set if it conforms to the schema. Bit `i % 8` of byte `i / 8`
+    /// belongs to document `i`.
+    pub validity: Vec<u8>,
+
+    /// Merkle root over [leaf_hash] of every document, in input order.
+    pub root: [u8; 32],
+}
+
+impl BatchJournal {
+    /// Encode the journal as the Solidity ABI encoding of the `BatchJournal` struct.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        AbiBatchJournal {
+            version: self.version,
+            schemaHash: self.schema_hash.into(),
+            draft: self.draft as u8,
+            count: self.count,
+            validity: self.validity.clone().into(),
+            root: self.root.into(),
+        }
+        .abi_encode()
+    }
+
+    /// Decode a journal produced by [BatchJournal::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        let abi = AbiBatchJournal::abi_decode(bytes, true).map_err(JournalError::Abi)?;
+        if abi.version != BATCH_JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion {
+                version: abi.version,
+                expected: BATCH_JOURNAL_VERSION,
+            });
+        }
+        Ok(BatchJournal {
+            version: abi.version,
+            schema_hash: abi.schemaHash.into(),
+            draft: SchemaDraft::try_from(abi.draft)?,
+            count: abi.count,
+            validity: abi.validity.into(),
+            root: abi.root.into(),
+        })
+    }
+
+    /// Whether document `index` conforms to the schema.
+    pub fn is_valid(&self, index: u32) -> bool {
+        let index = index as usize;
+        self.validity
+            .get(index / 8)
+            .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+    }
+
+    /// Number of documents that conform to the schema.
+    pub fn valid_count(&self) -> u32 {
+        self.validity.iter().map(|byte| byte.count_ones()).sum()
+    }
+}
+
+/// Pack per-document validity into the [BatchJournal::validity] bitmap.
+pub fn validity_bitmap(valid: &[bool]) -> Vec<u8> {
+    let mut bitmap = vec![0u8; valid.len().div_ceil(8)];
+    for (index, _) in valid.iter().enumerate().filter(|(_, valid)| **valid) {
+        bitmap[index / 8] |= 1 << (index % 8);
+    }
+    bitmap
+}
+
+/// The data hash of a batch document, as committed in its leaf.
+///
+/// This is the unsalted [data_commitment] of the document, so a record hashes the same in a batch
+/// as in a check_schema journal, whatever its formatting. A document that is not JSON has no
+/// canonical form, so its raw bytes are hashed instead.
+pub fn record_commitment(document: &[u8]) -> [u8; 32] {
+    match serde_json::from_slice::<Value>(document) {
+        Ok(data) => data_commitment(&data, None),
+        Err(_) => raw_commitment(document, None),
+    }
+}
+
+/// The Merkle leaf for a document with [record_commitment] `data_hash` and validity `valid`.
+pub fn leaf_hash(data_hash: &[u8; 32], valid: bool) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(data_hash)
+        .chain_update([valid as u8])
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// Hash one level of the tree into the next. An odd node out is carried up unchanged.
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// The Merkle root over `leaves`. The root of an empty batch is all zeroes.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Shows that one document is covered by a [BatchJournal].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct InclusionProof {
+    /// Position of the document in the batch.
+    pub index: u32,
+
+    /// [record_commitment] of the document.
+    pub data_hash: [u8; 32],
+
+    /// Whether the document conforms to the schema.
+    pub valid: bool,
+
+    /// Sibling hashes from the leaf up to the root. Levels where the node was carried up
+    /// without a sibling are skipped.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl InclusionProof {
+    /// Build the proof for `leaves[index]`, where `leaves` are the [leaf_hash]es of the batch.
+    ///
+    /// Returns `None` if `index` is out of range.
+    pub fn new(leaves: &[[u8; 32]], index: u32, data_hash: [u8; 32], valid: bool) -> Option<Self> {
+        if index as usize >= leaves.len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut position = index as usize;
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            let sibling = position ^ 1;
+            if sibling < level.len() {
+                siblings.push(level[sibling]);
+            }
+            level = next_level(&level);
+            position /= 2;
+        }
+        Some(InclusionProof {
+            index,
+            data_hash,
+            valid,
+            siblings,
+        })
+    }
+
+    /// Check the proof against the root and document count committed in `journal`.
+    pub fn verify(&self, journal: &BatchJournal) -> bool {
+        if self.index >= journal.count {
+            return false;
+        }
+        let mut hash = leaf_hash(&self.data_hash, self.valid);
+        let mut siblings = self.siblings.iter();
+        let mut position = self.index as usize;
+        let mut width = journal.count as usize;
+        while width > 1 {
+            let sibling = position ^ 1;
+            if sibling < width {
+                let Some(sibling_hash) = siblings.next() else {
+                    return false;
+                };
+                hash = match position % 2 {
+                    0 => node_hash(&hash, sibling_hash),
+                    _ => node_hash(sibling_hash, &hash),
+                };
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none() && hash == journal.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(count: u32) -> (Vec<[u8; 32]>, BatchJournal) {
+        let valid: Vec<bool> = (0..count).map(|i| i % 3 != 0).collect();
+        let leaves: Vec<_> = (0..count)
+            .map(|i| leaf_hash(&[i as u8; 32], valid[i as usize]))
+            .collect();
+        let journal = BatchJournal {
+            version: BATCH_JOURNAL_VERSION,
+            schema_hash: [0xaa; 32],
+            draft: SchemaDraft::Draft7,
+            count,
+            validity: validity_bitmap(&valid),
+            root: merkle_root(&leaves),
+        };
+        (leaves, journal)
+    }
+
+    #[test]
+    fn every_record_is_provable() {
+        for count in 1..=9 {
+            let (leaves, journal) = batch(count);
+            for index in 0..count {
+                let proof =
+                    InclusionProof::new(&leaves, index, [index as u8; 32], index % 3 != 0).unwrap();
+                assert!(proof.verify(&journal), "count {count} index {index}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_flipped_validity() {
+        let (leaves, journal) = batch(5);
+        let mut proof = InclusionProof::new(&leaves, 2, [2; 32], true).unwrap();
+        assert!(proof.verify(&journal));
+        proof.valid = false;
+        assert!(!proof.verify(&journal));
+    }
+
+    #[test]
+    fn validity_bits() {
+        let (_, journal) = batch(10);
+        assert_eq!(journal.validity.len(), 2);
+        assert_eq!(journal.valid_count(), 6);
+        assert!(!journal.is_valid(0));
+        assert!(journal.is_valid(8));
+        assert!(!journal.is_valid(9));
+        assert!(!journal.is_valid(10));
+    }
+
+    #[test]
+    fn record_commitments() {
+        assert_eq!(
+            record_commitment(br#"{ "b": 1, "a": 2 }"#),
+            data_commitment(&serde_json::json!({"a": 2, "b": 1}), None)
+        );
+        assert_eq!(
+            record_commitment(b"not json"),
+            <[u8; 32]>::from(Sha256::digest(b"not json"))
+        );
+    }
+
+    #[test]
+    fn abi_round_trip() {
+        let (_, journal) = batch(4);
+        assert_eq!(
+            BatchJournal::abi_decode(&journal.abi_encode()).unwrap(),
+            journal
+        );
+    }
+}
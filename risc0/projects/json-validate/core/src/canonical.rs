@@ -0,0 +1,183 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON Canonicalization Scheme (RFC 8785).
+//!
+//! Documents that differ only in whitespace, key order, string escapes or number spelling have
+//! the same canonical form, and therefore the same commitment.
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+/// Serialize `value` in its RFC 8785 canonical form.
+///
+/// Numbers are treated as IEEE 754 doubles, as the RFC requires, so integers beyond 2^53 lose
+/// precision.
+pub fn canonicalize(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(number) => write_number(
+            out,
+            number.as_f64().expect("JSON numbers are finite doubles"),
+        ),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            // Keys are ordered by their UTF-16 code units, which differs from byte order for
+            // characters outside the basic multilingual plane.
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, item);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Formats `n` as ECMAScript's `Number.prototype.toString` does.
+fn write_number(out: &mut String, n: f64) {
+    if n == 0.0 {
+        // Covers -0 as well.
+        out.push('0');
+        return;
+    }
+    if n < 0.0 {
+        out.push('-');
+    }
+
+    // `{:e}` gives the shortest digits that round-trip, e.g. `1.2345e-7`.
+    let scientific = format!("{:e}", n.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // Position of the decimal point relative to the start of `digits`.
+    let n = exponent.parse::<i32>().unwrap() + 1;
+
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        let (int, frac) = digits.split_at(n as usize);
+        write!(out, "{int}.{frac}").unwrap();
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat((-n) as usize));
+        out.push_str(&digits);
+    } else {
+        let (first, rest) = digits.split_at(1);
+        out.push_str(first);
+        if !rest.is_empty() {
+            write!(out, ".{rest}").unwrap();
+        }
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        write!(out, "e{sign}{}", (n - 1).abs()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(json: &str) -> String {
+        canonicalize(&serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn numbers() {
+        for (json, expected) in [
+            ("0", "0"),
+            ("-0", "0"),
+            ("23", "23"),
+            ("4.50", "4.5"),
+            ("0.002", "0.002"),
+            ("0.000001", "0.000001"),
+            ("1e-7", "1e-7"),
+            ("1e-27", "1e-27"),
+            ("1e20", "100000000000000000000"),
+            ("1e21", "1e+21"),
+            ("1e30", "1e+30"),
+            ("-1.5e300", "-1.5e+300"),
+            ("333333333.33333329", "333333333.3333333"),
+            ("9007199254740993", "9007199254740992"),
+        ] {
+            assert_eq!(canonical(json), expected, "{json}");
+        }
+    }
+
+    #[test]
+    fn strings_and_key_order() {
+        assert_eq!(
+            canonical(r#""\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/""#),
+            r#""€$\u000f\nA'B\"\\\\\"/""#
+        );
+        // Example from RFC 8785 section 3.2.3.
+        let sorted = canonical(
+            r#"{"\u20ac": 1, "\r": 2, "\ufb33": 3, "1": 4, "\ud83d\ude00": 5, "\u0080": 6, "\u00f6": 7}"#,
+        );
+        assert_eq!(
+            sorted,
+            "{\"\\r\":2,\"1\":4,\"\u{80}\":6,\"ö\":7,\"€\":1,\"😀\":5,\"\u{fb33}\":3}"
+        );
+    }
+
+    #[test]
+    fn whitespace_and_order_do_not_matter() {
+        assert_eq!(
+            canonical(r#"{ "name": "John Doe", "age": 23.0 }"#),
+            canonical(r#"{"age":23,"name":"John Doe"}"#)
+        );
+    }
+}
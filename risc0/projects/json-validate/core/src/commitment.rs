@@ -0,0 +1,82 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commitments to the data and schema recorded in the journals.
+//!
+//! Guests and hosts both go through these functions, so a holder of the data can reproduce the
+//! `data_hash` of a receipt and look it up. Both commitments hash the RFC 8785 canonical form,
+//! so they do not depend on whitespace or key order.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::canonicalize;
+
+/// Length of the salt mixed into a blinded data commitment.
+pub const SALT_LEN: usize = 32;
+
+/// The `data_hash` committed for `data`.
+///
+/// Without a salt this is SHA-256 of the canonical data. A low-entropy document such as
+/// `{"name": .., "age": ..}` can be recovered from that by brute force, so the host may supply
+/// a random salt, giving SHA-256(salt || canonical(data)). Only holders of the salt can then
+/// test a guess against the commitment. The salt has a fixed length so the concatenation is
+/// unambiguous.
+pub fn data_commitment(data: &Value, salt: Option<&[u8; SALT_LEN]>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    if let Some(salt) = salt {
+        hasher.update(salt);
+    }
+    hasher.update(canonicalize(data).as_bytes());
+    hasher.finalize().into()
+}
+
+/// The commitment to `bytes` that have no canonical form, such as data that is not JSON.
+///
+/// This is SHA-256 of the raw bytes, salted the same way as [data_commitment].
+pub fn raw_commitment(bytes: &[u8], salt: Option<&[u8; SALT_LEN]>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    if let Some(salt) = salt {
+        hasher.update(salt);
+    }
+    hasher.update(bytes);
+    hasher.finalize().into()
+}
+
+/// The `schema_hash` committed for `schema`: SHA-256 of the canonical schema.
+pub fn schema_commitment(schema: &Value) -> [u8; 32] {
+    Sha256::digest(canonicalize(schema).as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn salt_blinds_the_commitment() {
+        let data = json!({"name": "John Doe", "age": 23});
+        let unsalted = data_commitment(&data, None);
+        assert_eq!(
+            unsalted,
+            <[u8; 32]>::from(Sha256::digest(r#"{"age":23,"name":"John Doe"}"#))
+        );
+        assert_ne!(data_commitment(&data, Some(&[7; SALT_LEN])), unsalted);
+        assert_ne!(
+            data_commitment(&data, Some(&[7; SALT_LEN])),
+            data_commitment(&data, Some(&[8; SALT_LEN]))
+        );
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::JournalError;
+
+/// JSON Schema specification the schema was compiled against.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[repr(u8)]
+pub enum SchemaDraft {
+    Draft4 = 4,
+    Draft6 = 6,
+    Draft7 = 7,
+    Draft201909 = 19,
+    Draft202012 = 20,
+}
+
+impl SchemaDraft {
+    /// Draft used when the schema has no `$schema` keyword and the host gives no override.
+    pub const DEFAULT: SchemaDraft = SchemaDraft::Draft7;
+
+    /// Map a `$schema` meta-schema URI to its draft.
+    ///
+    /// Both `http` and `https` and an optional trailing `#` are accepted.
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        let uri = uri
+            .trim()
+            .trim_start_matches("http://")
+            .trim_start_matches("https://")
+            .trim_end_matches('#');
+        match uri {
+            "json-schema.org/draft-04/schema" => Some(SchemaDraft::Draft4),
+            "json-schema.org/draft-06/schema" => Some(SchemaDraft::Draft6),
+            "json-schema.org/draft-07/schema" => Some(SchemaDraft::Draft7),
+            "json-schema.org/draft/2019-09/schema" => Some(SchemaDraft::Draft201909),
+            "json-schema.org/draft/2020-12/schema" => Some(SchemaDraft::Draft202012),
+            _ => None,
+        }
+    }
+
+    /// Choose the draft for a schema whose `$schema` keyword is `meta_schema`.
+    ///
+    /// A host-supplied `requested` draft takes precedence over `$schema`, and [Self::DEFAULT]
+    /// applies when neither is present. Unknown meta-schemas are rejected rather than silently
+    /// validated under the default draft's semantics.
+    pub fn select(
+        meta_schema: Option<&str>,
+        requested: Option<SchemaDraft>,
+    ) -> Result<Self, DraftError> {
+        match (requested, meta_schema) {
+            (Some(draft), _) => Ok(draft),
+            (None, Some(uri)) => {
+                Self::from_uri(uri).ok_or_else(|| DraftError::UnknownMetaSchema(uri.to_string()))
+            }
+            (None, None) => Ok(Self::DEFAULT),
+        }
+    }
+}
+
+impl TryFrom<u8> for SchemaDraft {
+    type Error = JournalError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            4 => Ok(SchemaDraft::Draft4),
+            6 => Ok(SchemaDraft::Draft6),
+            7 => Ok(SchemaDraft::Draft7),
+            19 => Ok(SchemaDraft::Draft201909),
+            20 => Ok(SchemaDraft::Draft202012),
+            _ => Err(JournalError::UnknownDraft(value)),
+        }
+    }
+}
+
+impl fmt::Display for SchemaDraft {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SchemaDraft::Draft4 => "4",
+            SchemaDraft::Draft6 => "6",
+            SchemaDraft::Draft7 => "7",
+            SchemaDraft::Draft201909 => "2019-09",
+            SchemaDraft::Draft202012 => "2020-12",
+        })
+    }
+}
+
+impl FromStr for SchemaDraft {
+    type Err = DraftError;
+
+    /// Parse the names printed by `Display`: `4`, `6`, `7`, `2019-09` or `2020-12`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(SchemaDraft::Draft4),
+            "6" => Ok(SchemaDraft::Draft6),
+            "7" => Ok(SchemaDraft::Draft7),
+            "2019-09" => Ok(SchemaDraft::Draft201909),
+            "2020-12" => Ok(SchemaDraft::Draft202012),
+            _ => Err(DraftError::UnknownName(s.to_string())),
+        }
+    }
+}
+
+/// Errors returned when choosing a [SchemaDraft].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DraftError {
+    /// `$schema` names a meta-schema that is not a known JSON Schema draft.
+    UnknownMetaSchema(String),
+
+    /// A draft name that is not one of `4`, `6`, `7`, `2019-09` or `2020-12`.
+    UnknownName(String),
+}
+
+impl fmt::Display for DraftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DraftError::UnknownMetaSchema(uri) => write!(f, "unknown $schema URI '{uri}'"),
+            DraftError::UnknownName(name) => write!(
+                f,
+                "unknown draft '{name}', expected one of 4, 6, 7, 2019-09 or 2020-12"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DraftError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_draft() {
+        let draft4 = Some("http://json-schema.org/draft-04/schema#");
+        assert_eq!(SchemaDraft::select(None, None), Ok(SchemaDraft::Draft7));
+        assert_eq!(SchemaDraft::select(draft4, None), Ok(SchemaDraft::Draft4));
+        assert_eq!(
+            SchemaDraft::select(draft4, Some(SchemaDraft::Draft6)),
+            Ok(SchemaDraft::Draft6)
+        );
+    }
+
+    #[test]
+    fn select_recent_drafts() {
+        let draft2020 = Some("https://json-schema.org/draft/2020-12/schema");
+        assert_eq!(
+            SchemaDraft::select(draft2020, None),
+            Ok(SchemaDraft::Draft202012)
+        );
+        let draft2019 = Some("https://json-schema.org/draft/2019-09/schema#");
+        assert_eq!(
+            SchemaDraft::select(draft2019, None),
+            Ok(SchemaDraft::Draft201909)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_meta_schemas() {
+        assert!(matches!(
+            SchemaDraft::select(Some("https://example.com/schema"), None),
+            Err(DraftError::UnknownMetaSchema(_))
+        ));
+    }
+}
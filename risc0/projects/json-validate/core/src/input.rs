@@ -0,0 +1,96 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{SchemaDraft, SALT_LEN};
+
+/// Input written by the host and read by the check_schema guest.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SchemaInput {
+    /// The JSON document to validate.
+    pub data: String,
+
+    /// The JSON schema to validate against.
+    pub schema: String,
+
+    /// Overrides the schema's `$schema` keyword when set.
+    pub draft: Option<SchemaDraft>,
+
+    /// JSON pointers to fields whose values are committed to the journal.
+    pub disclose: Vec<String>,
+
+    /// A [Predicate](crate::Predicate) in text form, evaluated over the data in the guest.
+    pub predicate: Option<String>,
+
+    /// Salt for the data commitment, see [data_commitment](crate::data_commitment).
+    pub salt: Option<[u8; SALT_LEN]>,
+}
+
+/// Input written by the host and read by the check_fixed_schema guest.
+///
+/// The same as [SchemaInput], without the schema and draft the guest has built in.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct FixedSchemaInput {
+    /// The JSON document to validate.
+    pub data: String,
+
+    /// JSON pointers to fields whose values are committed to the journal.
+    pub disclose: Vec<String>,
+
+    /// A [Predicate](crate::Predicate) in text form, evaluated over the data in the guest.
+    pub predicate: Option<String>,
+
+    /// Salt for the data commitment, see [data_commitment](crate::data_commitment).
+    pub salt: Option<[u8; SALT_LEN]>,
+}
+
+impl FixedSchemaInput {
+    /// The [SchemaInput] for validating against `schema`, the schema built into the guest.
+    pub fn with_schema(self, schema: &str) -> SchemaInput {
+        SchemaInput {
+            data: self.data,
+            schema: schema.to_string(),
+            draft: None,
+            disclose: self.disclose,
+            predicate: self.predicate,
+            salt: self.salt,
+        }
+    }
+}
+
+/// Input written by the host and read by the check_schema_batch guest.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct BatchInput {
+    /// The JSON schema every document is validated against.
+    pub schema: String,
+
+    /// The documents to validate, in the order of the batch's leaves and validity bits.
+    pub documents: Vec<String>,
+
+    /// Overrides the schema's `$schema` keyword when set.
+    pub draft: Option<SchemaDraft>,
+}
+
+/// Input written by the host and read by the check_schema_stream guest.
+///
+/// The NDJSON records themselves follow on the guest's stdin.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct StreamInput {
+    /// The JSON schema every record is validated against.
+    pub schema: String,
+
+    /// Overrides the schema's `$schema` keyword when set.
+    pub draft: Option<SchemaDraft>,
+}
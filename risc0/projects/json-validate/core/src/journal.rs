@@ -0,0 +1,322 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use alloy_sol_types::{sol, SolValue};
+use serde::{Deserialize, Serialize};
+
+use crate::SchemaDraft;
+
+/// Version of the journal layout committed by the check_schema guest.
+///
+/// Bump this whenever a field is added, removed or reordered in [Journal].
+pub const JOURNAL_VERSION: u8 = 5;
+
+sol! {
+    struct AbiValidationFailure {
+        string instancePath;
+        string keyword;
+    }
+
+    struct AbiDisclosure {
+        string pointer;
+        bool present;
+        string value;
+    }
+
+    struct AbiJournal {
+        uint8 version;
+        bytes32 dataHash;
+        bool salted;
+        bytes32 schemaHash;
+        uint8 draft;
+        bool valid;
+        AbiValidationFailure[] errors;
+        AbiDisclosure[] disclosed;
+        string predicate;
+        bool predicateHolds;
+    }
+}
+
+/// A single schema violation found by the guest.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidationFailure {
+    /// JSON pointer to the offending value in the document, e.g. `/address/zip`.
+    pub instance_path: String,
+
+    /// The schema keyword that rejected the value, e.g. `required` or `type`.
+    pub keyword: String,
+}
+
+/// A field revealed by the guest at the host's request.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Disclosure {
+    /// JSON pointer to the disclosed field, e.g. `/address/state`.
+    pub pointer: String,
+
+    /// The field's value as compact JSON text, or `None` if the pointer does not resolve.
+    pub value: Option<String>,
+}
+
+/// The result of evaluating a [Predicate](crate::Predicate) inside the guest.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PredicateOutcome {
+    /// The predicate in its canonical text form.
+    pub predicate: String,
+
+    /// Whether the predicate holds for the data.
+    pub holds: bool,
+}
+
+/// The journal committed by the check_schema guest.
+///
+/// An invalid document is a provable outcome too: `valid` is false and `errors` lists every
+/// violation reported by the validator.
+///
+/// The guest commits the Solidity ABI encoding so application contracts can
+/// `abi.decode(journal, (Journal))`. The risc0 serde encoding is available for hosts that pass
+/// the journal to another guest via `env::read`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Journal {
+    /// Layout version, always [JOURNAL_VERSION] for journals produced by this crate.
+    pub version: u8,
+
+    /// Commitment to the canonical JSON of the data, salted if `salted` is set, see
+    /// [data_commitment](crate::data_commitment). Data that is not JSON has no canonical form,
+    /// so its raw bytes are committed instead, salted the same way.
+    pub data_hash: [u8; 32],
+
+    /// Whether `data_hash` is blinded with a host-supplied salt.
+    pub salted: bool,
+
+    /// Commitment to the schema, see [schema_commitment](crate::schema_commitment).
+    pub schema_hash: [u8; 32],
+
+    /// The draft the schema was compiled against.
+    pub draft: SchemaDraft,
+
+    /// Whether the data conforms to the schema.
+    pub valid: bool,
+
+    /// The violations found, empty when `valid` is true.
+    pub errors: Vec<ValidationFailure>,
+
+    /// The fields the host asked to reveal, in request order. Nothing else about the data's
+    /// values is committed.
+    pub disclosed: Vec<Disclosure>,
+
+    /// The predicate the host asked the guest to evaluate, if any. Its operands are public but
+    /// the values it was evaluated against are not.
+    pub predicate: Option<PredicateOutcome>,
+}
+
+impl Journal {
+    /// Encode the journal as the Solidity ABI encoding of the `Journal` struct.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        AbiJournal {
+            version: self.version,
+            dataHash: self.data_hash.into(),
+            salted: self.salted,
+            schemaHash: self.schema_hash.into(),
+            draft: self.draft as u8,
+            valid: self.valid,
+            errors: self
+                .errors
+                .iter()
+                .map(|error| AbiValidationFailure {
+                    instancePath: error.instance_path.clone(),
+                    keyword: error.keyword.clone(),
+                })
+                .collect(),
+            disclosed: self
+                .disclosed
+                .iter()
+                .map(|disclosure| AbiDisclosure {
+                    pointer: disclosure.pointer.clone(),
+                    present: disclosure.value.is_some(),
+                    value: disclosure.value.clone().unwrap_or_default(),
+                })
+                .collect(),
+            // A parsed predicate is never empty, so the empty string stands for no predicate.
+            predicate: self
+                .predicate
+                .as_ref()
+                .map(|outcome| outcome.predicate.clone())
+                .unwrap_or_default(),
+            predicateHolds: self.predicate.as_ref().is_some_and(|outcome| outcome.holds),
+        }
+        .abi_encode()
+    }
+
+    /// Decode a journal produced by [Journal::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        let abi = AbiJournal::abi_decode(bytes, true).map_err(JournalError::Abi)?;
+        check_version(abi.version)?;
+        Ok(Journal {
+            version: abi.version,
+            data_hash: abi.dataHash.into(),
+            salted: abi.salted,
+            schema_hash: abi.schemaHash.into(),
+            draft: SchemaDraft::try_from(abi.draft)?,
+            valid: abi.valid,
+            errors: abi
+                .errors
+                .into_iter()
+                .map(|error| ValidationFailure {
+                    instance_path: error.instancePath,
+                    keyword: error.keyword,
+                })
+                .collect(),
+            disclosed: abi
+                .disclosed
+                .into_iter()
+                .map(|disclosure| Disclosure {
+                    pointer: disclosure.pointer,
+                    value: disclosure.present.then_some(disclosure.value),
+                })
+                .collect(),
+            predicate: (!abi.predicate.is_empty()).then_some(PredicateOutcome {
+                predicate: abi.predicate,
+                holds: abi.predicateHolds,
+            }),
+        })
+    }
+
+    /// Encode the journal using the risc0 serde format, as `env::commit` would.
+    pub fn to_risc0_bytes(&self) -> Result<Vec<u8>, JournalError> {
+        let words = risc0_zkvm::serde::to_vec(self).map_err(JournalError::Serde)?;
+        Ok(words.iter().flat_map(|word| word.to_le_bytes()).collect())
+    }
+
+    /// Decode a journal produced by [Journal::to_risc0_bytes].
+    pub fn from_risc0_bytes(bytes: &[u8]) -> Result<Self, JournalError> {
+        let chunks = bytes.chunks_exact(4);
+        if !chunks.remainder().is_empty() {
+            return Err(JournalError::Length(bytes.len()));
+        }
+        let words: Vec<u32> = chunks
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        let journal: Journal =
+            risc0_zkvm::serde::from_slice(&words).map_err(JournalError::Serde)?;
+        check_version(journal.version)?;
+        Ok(journal)
+    }
+}
+
+fn check_version(version: u8) -> Result<(), JournalError> {
+    if version != JOURNAL_VERSION {
+        return Err(JournalError::UnsupportedVersion {
+            version,
+            expected: JOURNAL_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// Errors returned when decoding a [Journal].
+#[derive(Debug)]
+pub enum JournalError {
+    /// The bytes are not a valid ABI encoding of the journal struct.
+    Abi(alloy_sol_types::Error),
+
+    /// The bytes are not a valid risc0 serde encoding of the journal.
+    Serde(risc0_zkvm::serde::Error),
+
+    /// The risc0 serde encoding is not a whole number of words.
+    Length(usize),
+
+    /// The journal was produced by a guest with a different layout.
+    UnsupportedVersion { version: u8, expected: u8 },
+
+    /// The draft identifier does not name a known JSON Schema draft.
+    UnknownDraft(u8),
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalError::Abi(err) => write!(f, "invalid ABI journal: {err}"),
+            JournalError::Serde(err) => write!(f, "invalid risc0 serde journal: {err}"),
+            JournalError::Length(len) => {
+                write!(f, "journal length {len} is not a multiple of 4 bytes")
+            }
+            JournalError::UnsupportedVersion { version, expected } => write!(
+                f,
+                "unsupported journal version {version}, expected {expected}"
+            ),
+            JournalError::UnknownDraft(draft) => write!(f, "unknown schema draft id {draft}"),
+        }
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal() -> Journal {
+        Journal {
+            version: JOURNAL_VERSION,
+            data_hash: [1; 32],
+            salted: true,
+            schema_hash: [2; 32],
+            draft: SchemaDraft::Draft7,
+            valid: false,
+            errors: vec![ValidationFailure {
+                instance_path: "/age".to_string(),
+                keyword: "type".to_string(),
+            }],
+            disclosed: vec![
+                Disclosure {
+                    pointer: "/name".to_string(),
+                    value: Some("\"John Doe\"".to_string()),
+                },
+                Disclosure {
+                    pointer: "/email".to_string(),
+                    value: None,
+                },
+            ],
+            predicate: Some(PredicateOutcome {
+                predicate: "/age >= 18".to_string(),
+                holds: true,
+            }),
+        }
+    }
+
+    #[test]
+    fn abi_round_trip() {
+        let journal = journal();
+        assert_eq!(Journal::abi_decode(&journal.abi_encode()).unwrap(), journal);
+    }
+
+    #[test]
+    fn risc0_round_trip() {
+        let journal = journal();
+        let bytes = journal.to_risc0_bytes().unwrap();
+        assert_eq!(Journal::from_risc0_bytes(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut journal = journal();
+        journal.version = JOURNAL_VERSION + 1;
+        assert!(matches!(
+            Journal::abi_decode(&journal.abi_encode()),
+            Err(JournalError::UnsupportedVersion { .. })
+        ));
+    }
+}
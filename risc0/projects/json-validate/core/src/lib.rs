@@ -12,10 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-// use risc0_zkvm::sha::Digest;
-use serde::{Deserialize, Serialize};
+mod batch;
+mod canonical;
+mod commitment;
+mod draft;
+mod input;
+mod journal;
+mod predicate;
+mod stream;
+#[cfg(feature = "validate")]
+mod validate;
 
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-pub struct Outputs {
-    pub result: u32,
-}
+pub use batch::{
+    leaf_hash, merkle_root, record_commitment, validity_bitmap, BatchJournal, InclusionProof,
+    BATCH_JOURNAL_VERSION,
+};
+pub use canonical::canonicalize;
+pub use commitment::{data_commitment, raw_commitment, schema_commitment, SALT_LEN};
+pub use draft::{DraftError, SchemaDraft};
+pub use input::{BatchInput, FixedSchemaInput, SchemaInput, StreamInput};
+pub use journal::{
+    Disclosure, Journal, JournalError, PredicateOutcome, ValidationFailure, JOURNAL_VERSION,
+};
+pub use predicate::{Clause, Operator, Predicate, PredicateError};
+pub use stream::{ndjson_record, StreamJournal, STREAM_JOURNAL_VERSION};
+#[cfg(feature = "validate")]
+pub use validate::{check_schema, disclose, CompiledSchema, SchemaError, INVALID_DATA_KEYWORD};
@@ -0,0 +1,392 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A small predicate language over JSON pointers.
+//!
+//! A predicate is one or more clauses joined by `&&`. Each clause compares the value at a JSON
+//! pointer with a JSON literal:
+//!
+//! ```text
+//! /age >= 18 && /address/state in ["CA", "NY"] && /verified == true
+//! ```
+//!
+//! `<`, `<=`, `>` and `>=` compare numbers. `==` and `!=` compare any JSON values, with numbers
+//! compared by numeric value. Two integers are compared exactly, other numbers as `f64`. `in`
+//! takes a JSON array and tests membership. A clause whose pointer does not resolve is false,
+//! whatever its operator. Pointers end at the first whitespace or operator character, so keys
+//! containing whitespace, `=`, `!`, `<` or `>` cannot be addressed.
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use serde_json::Value;
+
+use crate::PredicateOutcome;
+
+/// A conjunction of [Clause]s, parsed from the text form with [str::parse].
+///
+/// The `Display` form is canonical: parsing it gives back the same predicate, so a verifier
+/// can normalise its expected predicate text before comparing it with a journal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Predicate {
+    /// The clauses, all of which must hold.
+    pub clauses: Vec<Clause>,
+}
+
+/// A single comparison between the value at a JSON pointer and a literal.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Clause {
+    /// JSON pointer to the tested field, e.g. `/address/state`.
+    pub pointer: String,
+
+    /// How the field is compared with `operand`.
+    pub operator: Operator,
+
+    /// The literal the field is compared with. An array for [Operator::In], a number for the
+    /// ordering operators.
+    pub operand: Value,
+}
+
+/// Comparison operators supported in a [Clause].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+}
+
+impl Predicate {
+    /// Whether every clause holds for `data`.
+    pub fn evaluate(&self, data: &Value) -> bool {
+        self.clauses.iter().all(|clause| clause.evaluate(data))
+    }
+
+    /// Evaluate against `data`, recording the canonical text for the journal.
+    pub fn outcome(&self, data: &Value) -> PredicateOutcome {
+        PredicateOutcome {
+            predicate: self.to_string(),
+            holds: self.evaluate(data),
+        }
+    }
+}
+
+impl Clause {
+    /// Whether the clause holds for `data`.
+    pub fn evaluate(&self, data: &Value) -> bool {
+        let Some(value) = data.pointer(&self.pointer) else {
+            return false;
+        };
+        match self.operator {
+            Operator::Eq => json_eq(value, &self.operand),
+            Operator::Ne => !json_eq(value, &self.operand),
+            Operator::In => self
+                .operand
+                .as_array()
+                .is_some_and(|items| items.iter().any(|item| json_eq(value, item))),
+            Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge => {
+                let Some(ordering) = compare_numbers(value, &self.operand) else {
+                    return false;
+                };
+                match self.operator {
+                    Operator::Lt => ordering.is_lt(),
+                    Operator::Le => ordering.is_le(),
+                    Operator::Gt => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                }
+            }
+        }
+    }
+
+    /// Parse one clause, which must make up the whole of `input`.
+    fn parse(input: &str) -> Result<Self, PredicateError> {
+        if !input.starts_with('/') {
+            return Err(PredicateError::ExpectedPointer(input.to_string()));
+        }
+        let end = input
+            .find(|c: char| c.is_whitespace() || "=!<>".contains(c))
+            .unwrap_or(input.len());
+        let (pointer, rest) = input.split_at(end);
+        let (operator, rest) = Operator::parse(rest.trim_start())?;
+
+        let rest = rest.trim_start();
+        let mut values = serde_json::Deserializer::from_str(rest).into_iter::<Value>();
+        let operand = match values.next() {
+            Some(Ok(operand)) => operand,
+            _ => return Err(PredicateError::ExpectedValue(rest.to_string())),
+        };
+        let rest = rest[values.byte_offset()..].trim_start();
+        if !rest.is_empty() {
+            return Err(PredicateError::ExpectedConjunction(rest.to_string()));
+        }
+
+        let well_typed = match operator {
+            Operator::In => operand.is_array(),
+            Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge => operand.is_number(),
+            Operator::Eq | Operator::Ne => true,
+        };
+        if !well_typed {
+            return Err(PredicateError::OperandType(operator, operand.to_string()));
+        }
+
+        Ok(Clause {
+            pointer: pointer.to_string(),
+            operator,
+            operand,
+        })
+    }
+}
+
+impl Operator {
+    fn as_str(self) -> &'static str {
+        match self {
+            Operator::Eq => "==",
+            Operator::Ne => "!=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::In => "in",
+        }
+    }
+
+    fn parse(input: &str) -> Result<(Self, &str), PredicateError> {
+        // Two-character operators first, so `<=` is not read as `<` followed by `=`.
+        const OPERATORS: [Operator; 7] = [
+            Operator::Eq,
+            Operator::Ne,
+            Operator::Le,
+            Operator::Ge,
+            Operator::Lt,
+            Operator::Gt,
+            Operator::In,
+        ];
+        OPERATORS
+            .into_iter()
+            .find_map(|operator| {
+                input
+                    .strip_prefix(operator.as_str())
+                    .map(|rest| (operator, rest))
+            })
+            .ok_or_else(|| PredicateError::ExpectedOperator(input.to_string()))
+    }
+}
+
+/// JSON equality, except that numbers are compared by value so `18` equals `18.0`.
+fn json_eq(lhs: &Value, rhs: &Value) -> bool {
+    match compare_numbers(lhs, rhs) {
+        Some(ordering) => ordering.is_eq(),
+        None => lhs == rhs,
+    }
+}
+
+/// Orders two numbers, or returns `None` if either is not a number.
+///
+/// Integers are compared exactly, since `f64` cannot represent every integer above 2^53.
+fn compare_numbers(lhs: &Value, rhs: &Value) -> Option<Ordering> {
+    let integer = |value: &Value| {
+        value
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| value.as_u64().map(i128::from))
+    };
+    match (integer(lhs), integer(rhs)) {
+        (Some(lhs), Some(rhs)) => Some(lhs.cmp(&rhs)),
+        _ => lhs.as_f64()?.partial_cmp(&rhs.as_f64()?),
+    }
+}
+
+/// Split a predicate into its clauses at each `&&` outside a JSON string.
+///
+/// This runs before any literal is parsed, so `/age >= 18&&/verified == true` splits cleanly
+/// even though `18&&` is not a JSON number.
+fn split_clauses(s: &str) -> Vec<&str> {
+    let mut clauses = Vec::new();
+    let mut start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = s.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '&' && chars.next_if(|(_, c)| *c == '&').is_some() {
+            clauses.push(&s[start..index]);
+            start = index + 2;
+        }
+    }
+    clauses.push(&s[start..]);
+    clauses
+}
+
+impl FromStr for Predicate {
+    type Err = PredicateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let clauses = split_clauses(s)
+            .into_iter()
+            .map(|clause| Clause::parse(clause.trim()))
+            .collect::<Result<_, _>>()?;
+        Ok(Predicate { clauses })
+    }
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, clause) in self.clauses.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" && ")?;
+            }
+            write!(f, "{clause}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Clause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.pointer, self.operator, self.operand)
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Errors returned when parsing a [Predicate].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PredicateError {
+    /// A clause does not start with a JSON pointer.
+    ExpectedPointer(String),
+
+    /// A pointer is not followed by a known operator.
+    ExpectedOperator(String),
+
+    /// An operator is not followed by a JSON literal.
+    ExpectedValue(String),
+
+    /// Clauses must be separated by `&&`.
+    ExpectedConjunction(String),
+
+    /// `in` needs an array operand and the ordering operators need a number.
+    OperandType(Operator, String),
+}
+
+impl fmt::Display for PredicateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PredicateError::ExpectedPointer(at) => {
+                write!(f, "expected a JSON pointer starting with '/' at '{at}'")
+            }
+            PredicateError::ExpectedOperator(at) => {
+                write!(f, "expected one of ==, !=, <, <=, >, >= or in at '{at}'")
+            }
+            PredicateError::ExpectedValue(at) => write!(f, "expected a JSON value at '{at}'"),
+            PredicateError::ExpectedConjunction(at) => write!(f, "expected '&&' at '{at}'"),
+            PredicateError::OperandType(operator, operand) => match operator {
+                Operator::In => write!(f, "'in' needs a JSON array, got {operand}"),
+                _ => write!(f, "'{operator}' needs a number, got {operand}"),
+            },
+        }
+    }
+}
+
+impl std::error::Error for PredicateError {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn evaluate() {
+        let data = json!({"age": 23, "address": {"state": "NY"}, "verified": true});
+        let holds = |predicate: &str| predicate.parse::<Predicate>().unwrap().evaluate(&data);
+
+        assert!(holds("/age >= 18"));
+        assert!(holds("/age == 23.0"));
+        assert!(!holds("/age < 18"));
+        assert!(holds(
+            r#"/address/state in ["CA", "NY"] && /verified == true"#
+        ));
+        assert!(!holds(r#"/address/state in ["CA"]"#));
+        assert!(!holds("/missing != 1"));
+        assert!(!holds(r#"/address/state > 1"#));
+        assert!(holds("/age >= 18&&/verified == true"));
+    }
+
+    #[test]
+    fn integers_compare_exactly() {
+        // Both are 9007199254740992.0 as f64.
+        let data = json!({"id": 9007199254740993u64, "min": i64::MIN});
+        let holds = |predicate: &str| predicate.parse::<Predicate>().unwrap().evaluate(&data);
+
+        assert!(holds("/id > 9007199254740992"));
+        assert!(holds("/id != 9007199254740992"));
+        assert!(holds("/id in [9007199254740993]"));
+        assert!(holds("/min < 18446744073709551615"));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let predicate: Predicate = r#"  /age>=18   &&/state in [ "CA","NY" ]"#.parse().unwrap();
+        assert_eq!(
+            predicate.to_string(),
+            r#"/age >= 18 && /state in ["CA","NY"]"#
+        );
+        assert_eq!(
+            predicate.to_string().parse::<Predicate>().unwrap(),
+            predicate
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert!(matches!(
+            "age >= 18".parse::<Predicate>(),
+            Err(PredicateError::ExpectedPointer(_))
+        ));
+        assert!(matches!(
+            "/age ~ 18".parse::<Predicate>(),
+            Err(PredicateError::ExpectedOperator(_))
+        ));
+        assert!(matches!(
+            r#"/age >= "18""#.parse::<Predicate>(),
+            Err(PredicateError::OperandType(Operator::Ge, _))
+        ));
+        assert!(matches!(
+            "/age >= 18 /name == 1".parse::<Predicate>(),
+            Err(PredicateError::ExpectedConjunction(_))
+        ));
+        assert!(matches!(
+            "/age >= 18 &&".parse::<Predicate>(),
+            Err(PredicateError::ExpectedPointer(_))
+        ));
+        assert_eq!(
+            r#"/name == "a && b""#.parse::<Predicate>().unwrap().clauses[0].operand,
+            json!("a && b")
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Streaming validation of newline-delimited JSON.
+//!
+//! The check_schema_stream guest reads records from stdin one line at a time, so the input never
+//! has to fit in guest memory. The journal commits the SHA-256 of every byte read, which equals
+//! the SHA-256 of the NDJSON file the host streamed in.
+
+use alloy_sol_types::{sol, SolValue};
+use serde::{Deserialize, Serialize};
+
+use crate::{JournalError, SchemaDraft};
+
+/// Version of the journal layout committed by the check_schema_stream guest.
+///
+/// Bump this whenever a field is added, removed or reordered in [StreamJournal].
+pub const STREAM_JOURNAL_VERSION: u8 = 2;
+
+sol! {
+    struct AbiStreamJournal {
+        uint8 version;
+        bytes32 schemaHash;
+        uint8 draft;
+        uint64 count;
+        uint64 validCount;
+        bytes32 streamHash;
+    }
+}
+
+/// The journal committed by the check_schema_stream guest.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StreamJournal {
+    /// Layout version, always [STREAM_JOURNAL_VERSION] for journals produced by this crate.
+    pub version: u8,
+
+    /// Commitment to the schema, see [schema_commitment](crate::schema_commitment).
+    pub schema_hash: [u8; 32],
+
+    /// The draft the schema was compiled against.
+    pub draft: SchemaDraft,
+
+    /// Number of records in the stream. Blank lines are not records.
+    pub count: u64,
+
+    /// Number of records that conform to the schema.
+    pub valid_count: u64,
+
+    /// SHA-256 of the whole stream, including blank lines and line terminators.
+    pub stream_hash: [u8; 32],
+}
+
+impl StreamJournal {
+    /// Encode the journal as the Solidity ABI encoding of the `StreamJournal` struct.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        AbiStreamJournal {
+            version: self.version,
+            schemaHash: self.schema_hash.into(),
+            draft: self.draft as u8,
+            count: self.count,
+            validCount: self.valid_count,
+            streamHash: self.stream_hash.into(),
+        }
+        .abi_encode()
+    }
+
+    /// Decode a journal produced by [StreamJournal::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> Result<Self, JournalError> {
+        let abi = AbiStreamJournal::abi_decode(bytes, true).map_err(JournalError::Abi)?;
+        if abi.version != STREAM_JOURNAL_VERSION {
+            return Err(JournalError::UnsupportedVersion {
+                version: abi.version,
+                expected: STREAM_JOURNAL_VERSION,
+            });
+        }
+        Ok(StreamJournal {
+            version: abi.version,
+            schema_hash: abi.schemaHash.into(),
+            draft: SchemaDraft::try_from(abi.draft)?,
+            count: abi.count,
+            valid_count: abi.validCount,
+            stream_hash: abi.streamHash.into(),
+        })
+    }
+
+    /// Whether every record in the stream conforms to the schema.
+    pub fn all_valid(&self) -> bool {
+        self.valid_count == self.count
+    }
+}
+
+/// The record held by one line of an NDJSON stream, or `None` for a blank line.
+///
+/// `line` may include its `\n` or `\r\n` terminator.
+pub fn ndjson_record(line: &[u8]) -> Option<&[u8]> {
+    let record = line.strip_suffix(b"\n").unwrap_or(line);
+    let record = record.strip_suffix(b"\r").unwrap_or(record);
+    if record.iter().all(u8::is_ascii_whitespace) {
+        return None;
+    }
+    Some(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records() {
+        assert_eq!(ndjson_record(b"{\"a\":1}\n"), Some(&b"{\"a\":1}"[..]));
+        assert_eq!(ndjson_record(b"{\"a\":1}\r\n"), Some(&b"{\"a\":1}"[..]));
+        assert_eq!(ndjson_record(b"{\"a\":1}"), Some(&b"{\"a\":1}"[..]));
+        assert_eq!(ndjson_record(b" \t\r\n"), None);
+        assert_eq!(ndjson_record(b""), None);
+    }
+
+    #[test]
+    fn abi_round_trip() {
+        let journal = StreamJournal {
+            version: STREAM_JOURNAL_VERSION,
+            schema_hash: [1; 32],
+            draft: SchemaDraft::Draft4,
+            count: 3,
+            valid_count: 2,
+            stream_hash: [2; 32],
+        };
+        assert!(!journal.all_valid());
+        assert_eq!(
+            StreamJournal::abi_decode(&journal.abi_encode()).unwrap(),
+            journal
+        );
+    }
+}
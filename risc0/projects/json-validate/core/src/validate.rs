@@ -0,0 +1,306 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Schema compilation and validation shared by the guests.
+
+use std::fmt;
+
+use jsonschema::{error::ValidationErrorKind, Draft, JSONSchema};
+use serde_json::Value;
+
+use crate::{
+    data_commitment, raw_commitment, schema_commitment, Disclosure, DraftError, Journal, Predicate,
+    PredicateOutcome, SchemaDraft, SchemaInput, ValidationFailure, JOURNAL_VERSION,
+};
+
+/// `keyword` of the single failure committed when the data is not valid JSON.
+pub const INVALID_DATA_KEYWORD: &str = "invalidJson";
+
+/// A schema compiled once and used to validate any number of documents.
+pub struct CompiledSchema {
+    inner: JSONSchema,
+    draft: SchemaDraft,
+    schema_hash: [u8; 32],
+}
+
+impl CompiledSchema {
+    /// Parse and compile the schema text `schema`, as every guest does with its input.
+    ///
+    /// `requested` overrides the schema's `$schema` keyword. Hosts call this before proving, so
+    /// that a schema the guest would panic on is reported with the same error.
+    pub fn parse(schema: &str, requested: Option<SchemaDraft>) -> Result<Self, SchemaError> {
+        let schema: Value =
+            serde_json::from_str(schema).map_err(|err| SchemaError::Json(err.to_string()))?;
+        Self::compile(&schema, requested)
+    }
+
+    /// Compile `schema`, choosing the draft with [SchemaDraft::select].
+    ///
+    /// `requested` overrides the schema's `$schema` keyword.
+    pub fn compile(schema: &Value, requested: Option<SchemaDraft>) -> Result<Self, SchemaError> {
+        let meta_schema = schema.get("$schema").and_then(Value::as_str);
+        let draft = SchemaDraft::select(meta_schema, requested).map_err(SchemaError::Draft)?;
+        let validator_draft = match draft {
+            SchemaDraft::Draft4 => Draft::Draft4,
+            SchemaDraft::Draft6 => Draft::Draft6,
+            SchemaDraft::Draft7 => Draft::Draft7,
+            SchemaDraft::Draft201909 => Draft::Draft201909,
+            SchemaDraft::Draft202012 => Draft::Draft202012,
+        };
+        let inner = JSONSchema::options()
+            .with_draft(validator_draft)
+            .compile(schema)
+            .map_err(|err| SchemaError::Invalid(format!("{err:?}")))?;
+        Ok(CompiledSchema {
+            inner,
+            draft,
+            schema_hash: schema_commitment(schema),
+        })
+    }
+
+    /// The draft the schema was compiled against.
+    pub fn draft(&self) -> SchemaDraft {
+        self.draft
+    }
+
+    /// The [schema_commitment] of the compiled schema.
+    pub fn schema_hash(&self) -> [u8; 32] {
+        self.schema_hash
+    }
+
+    /// Validate `data`, returning every violation found. An empty list means `data` conforms.
+    pub fn validate(&self, data: &Value) -> Vec<ValidationFailure> {
+        match self.inner.validate(data) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .map(|error| ValidationFailure {
+                    instance_path: error.instance_path.to_string(),
+                    keyword: keyword(&error.kind).to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Look up each of `pointers` in `data` for the journal's `disclosed` list.
+pub fn disclose(data: &Value, pointers: &[String]) -> Vec<Disclosure> {
+    pointers
+        .iter()
+        .map(|pointer| Disclosure {
+            pointer: pointer.clone(),
+            value: data.pointer(pointer).map(Value::to_string),
+        })
+        .collect()
+}
+
+/// Validate `input` as the check_schema guests do, returning the journal to commit.
+///
+/// Data that is not JSON is a provable outcome rather than a guest panic. It has no canonical
+/// form, so `data_hash` then covers its raw bytes (salted as usual), `valid` is false, `errors`
+/// holds one failure with [INVALID_DATA_KEYWORD], nothing is disclosed and the predicate does
+/// not hold.
+///
+/// # Panics
+///
+/// Panics if the schema fails [CompiledSchema::parse] or `input.predicate` does not parse.
+/// Hosts check both before proving.
+pub fn check_schema(input: &SchemaInput) -> Journal {
+    let compiled =
+        CompiledSchema::parse(&input.schema, input.draft).unwrap_or_else(|err| panic!("{err}"));
+    let predicate = input.predicate.as_deref().map(|text| {
+        text.parse::<Predicate>()
+            .unwrap_or_else(|err| panic!("{err}"))
+    });
+
+    let data = serde_json::from_str::<Value>(&input.data);
+    let errors = match &data {
+        Ok(data) => compiled.validate(data),
+        Err(_) => vec![ValidationFailure {
+            instance_path: String::new(),
+            keyword: INVALID_DATA_KEYWORD.to_string(),
+        }],
+    };
+
+    Journal {
+        version: JOURNAL_VERSION,
+        // Both hashes cover the canonical JSON, so formatting does not change them. The data hash
+        // is blinded with the host's salt, if any, so low-entropy data cannot be brute-forced.
+        data_hash: match &data {
+            Ok(data) => data_commitment(data, input.salt.as_ref()),
+            Err(_) => raw_commitment(input.data.as_bytes(), input.salt.as_ref()),
+        },
+        salted: input.salt.is_some(),
+        schema_hash: compiled.schema_hash(),
+        draft: compiled.draft(),
+        valid: errors.is_empty(),
+        errors,
+        // Only the requested fields are revealed; the rest of the data stays behind data_hash.
+        disclosed: match &data {
+            Ok(data) => disclose(data, &input.disclose),
+            Err(_) => Vec::new(),
+        },
+        // Only the predicate's text and result are committed, not the values it was evaluated on.
+        predicate: predicate.map(|predicate| match &data {
+            Ok(data) => predicate.outcome(data),
+            Err(_) => PredicateOutcome {
+                predicate: predicate.to_string(),
+                holds: false,
+            },
+        }),
+    }
+}
+
+/// Errors returned when a schema cannot be compiled.
+#[derive(Debug)]
+pub enum SchemaError {
+    /// The schema is not valid JSON.
+    Json(String),
+
+    /// The schema's `$schema` names an unknown draft.
+    Draft(DraftError),
+
+    /// The schema is not a valid JSON Schema document.
+    Invalid(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Json(err) => write!(f, "schema is not valid JSON: {err}"),
+            SchemaError::Draft(err) => write!(f, "{err}"),
+            SchemaError::Invalid(err) => write!(f, "invalid schema: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// Maps a validation error to the name of the schema keyword that produced it.
+fn keyword(kind: &ValidationErrorKind) -> &'static str {
+    match kind {
+        ValidationErrorKind::AdditionalItems { .. } => "additionalItems",
+        ValidationErrorKind::AdditionalProperties { .. } => "additionalProperties",
+        ValidationErrorKind::AnyOf => "anyOf",
+        ValidationErrorKind::Constant { .. } => "const",
+        ValidationErrorKind::Contains => "contains",
+        ValidationErrorKind::ContentEncoding { .. } => "contentEncoding",
+        ValidationErrorKind::ContentMediaType { .. } => "contentMediaType",
+        ValidationErrorKind::Enum { .. } => "enum",
+        ValidationErrorKind::ExclusiveMaximum { .. } => "exclusiveMaximum",
+        ValidationErrorKind::ExclusiveMinimum { .. } => "exclusiveMinimum",
+        ValidationErrorKind::FalseSchema => "false",
+        ValidationErrorKind::Format { .. } => "format",
+        ValidationErrorKind::MaxItems { .. } => "maxItems",
+        ValidationErrorKind::Maximum { .. } => "maximum",
+        ValidationErrorKind::MaxLength { .. } => "maxLength",
+        ValidationErrorKind::MaxProperties { .. } => "maxProperties",
+        ValidationErrorKind::MinItems { .. } => "minItems",
+        ValidationErrorKind::Minimum { .. } => "minimum",
+        ValidationErrorKind::MinLength { .. } => "minLength",
+        ValidationErrorKind::MinProperties { .. } => "minProperties",
+        ValidationErrorKind::MultipleOf { .. } => "multipleOf",
+        ValidationErrorKind::Not { .. } => "not",
+        ValidationErrorKind::OneOfMultipleValid => "oneOf",
+        ValidationErrorKind::OneOfNotValid => "oneOf",
+        ValidationErrorKind::Pattern { .. } => "pattern",
+        ValidationErrorKind::PropertyNames { .. } => "propertyNames",
+        ValidationErrorKind::Required { .. } => "required",
+        ValidationErrorKind::Type { .. } => "type",
+        ValidationErrorKind::UnevaluatedProperties { .. } => "unevaluatedProperties",
+        ValidationErrorKind::UniqueItems => "uniqueItems",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    #[test]
+    fn draft_2020_12() {
+        let schema: Value =
+            serde_json::from_str(include_str!("../../res/schema_complex_obj.json")).unwrap();
+        let data: Value =
+            serde_json::from_str(include_str!("../../res/data_complex_obj.json")).unwrap();
+        let compiled = CompiledSchema::compile(&schema, None).unwrap();
+        assert_eq!(compiled.draft(), SchemaDraft::Draft202012);
+        assert_eq!(compiled.validate(&data), Vec::new());
+    }
+
+    #[test]
+    fn unevaluated_properties() {
+        // unevaluatedProperties only exists since 2019-09, so draft 7 ignores it.
+        let schema = json!({
+            "properties": { "a": {} },
+            "unevaluatedProperties": false
+        });
+        let data = json!({ "a": 1, "b": 2 });
+        let errors = CompiledSchema::compile(&schema, Some(SchemaDraft::Draft202012))
+            .unwrap()
+            .validate(&data);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].keyword, "unevaluatedProperties");
+        let draft7 = CompiledSchema::compile(&schema, Some(SchemaDraft::Draft7)).unwrap();
+        assert!(draft7.validate(&data).is_empty());
+    }
+
+    fn input(data: &str, schema: &str) -> SchemaInput {
+        SchemaInput {
+            data: data.to_string(),
+            schema: schema.to_string(),
+            disclose: vec!["/age".to_string()],
+            predicate: Some("/age >= 18".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_schema_commits_invalid_data() {
+        let journal = check_schema(&input("{\"age\": ", r#"{"type": "object"}"#));
+        assert!(!journal.valid);
+        assert_eq!(journal.errors.len(), 1);
+        assert_eq!(journal.errors[0].keyword, INVALID_DATA_KEYWORD);
+        assert_eq!(
+            journal.data_hash,
+            <[u8; 32]>::from(Sha256::digest("{\"age\": "))
+        );
+        assert!(journal.disclosed.is_empty());
+        assert!(!journal.predicate.unwrap().holds);
+    }
+
+    #[test]
+    fn schema_errors() {
+        assert!(matches!(
+            CompiledSchema::parse("{\"type\"", None),
+            Err(SchemaError::Json(_))
+        ));
+        assert!(matches!(
+            CompiledSchema::parse(r#"{"$schema": "https://example.com/schema"}"#, None),
+            Err(SchemaError::Draft(_))
+        ));
+        assert!(matches!(
+            CompiledSchema::parse(r#"{"type": 1}"#, None),
+            Err(SchemaError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "schema is not valid JSON")]
+    fn check_schema_panics_on_invalid_schema() {
+        check_schema(&input("{}", "{\"type\""));
+    }
+}
@@ -12,6 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{env, fs, path::PathBuf};
+
+/// Environment variable selecting the schema baked into the check_fixed_schema guest.
+const FIXED_SCHEMA_ENV: &str = "JSON_VALIDATE_FIXED_SCHEMA";
+
 fn main() {
+    // The guest build script reads the same variable, so both embed the same schema.
+    println!("cargo:rerun-if-env-changed={FIXED_SCHEMA_ENV}");
+    let schema_path = match env::var_os(FIXED_SCHEMA_ENV) {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap())
+            .join("fixed-guest/src/res/schema.json"),
+    };
+    // A relative path would be resolved against whichever crate is being built, and this crate
+    // and the guest would disagree on it.
+    assert!(
+        schema_path.is_absolute(),
+        "{FIXED_SCHEMA_ENV} must be an absolute path, got {}",
+        schema_path.display()
+    );
+    println!("cargo:rerun-if-changed={}", schema_path.display());
+
+    // Catch a malformed schema here rather than as a guest panic at proving time.
+    let schema = fs::read_to_string(&schema_path)
+        .unwrap_or_else(|err| panic!("{FIXED_SCHEMA_ENV}={}: {err}", schema_path.display()));
+    serde_json::from_str::<serde_json::Value>(&schema)
+        .unwrap_or_else(|err| panic!("{} is not valid JSON: {err}", schema_path.display()));
+    println!(
+        "cargo:rustc-env=CHECK_FIXED_SCHEMA_PATH={}",
+        schema_path.display()
+    );
+
     risc0_build::embed_methods();
 }
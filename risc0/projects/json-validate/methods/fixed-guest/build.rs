@@ -0,0 +1,38 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{env, path::PathBuf};
+
+/// Environment variable selecting the schema to embed, inherited from the methods build.
+const FIXED_SCHEMA_ENV: &str = "JSON_VALIDATE_FIXED_SCHEMA";
+
+fn main() {
+    println!("cargo:rerun-if-env-changed={FIXED_SCHEMA_ENV}");
+    let schema_path = match env::var_os(FIXED_SCHEMA_ENV) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("src/res/schema.json")
+        }
+    };
+    assert!(
+        schema_path.is_absolute(),
+        "{FIXED_SCHEMA_ENV} must be an absolute path, got {}",
+        schema_path.display()
+    );
+    println!("cargo:rerun-if-changed={}", schema_path.display());
+    println!(
+        "cargo:rustc-env=FIXED_SCHEMA_PATH={}",
+        schema_path.display()
+    );
+}
@@ -0,0 +1,31 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use json_validate_core::{check_schema, FixedSchemaInput};
+use risc0_zkvm::guest::env;
+
+// The schema is embedded at build time (see build.rs, which takes its path from
+// JSON_VALIDATE_FIXED_SCHEMA), so the image ID alone identifies it and verifiers do not need to
+// check `schema_hash` in the journal.
+const SCHEMA: &str = include_str!(env!("FIXED_SCHEMA_PATH"));
+
+fn main() {
+    let input: FixedSchemaInput = env::read();
+
+    // Validate the data against the embedded schema. The draft comes from its `$schema` keyword.
+    let journal = check_schema(&input.with_schema(SCHEMA));
+
+    // Commit the journal that will be received by the application contract.
+    // Journal is encoded using Solidity ABI for easy decoding in the app contract.
+    env::commit_slice(&journal.abi_encode());
+}
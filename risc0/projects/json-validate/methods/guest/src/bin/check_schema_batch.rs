@@ -0,0 +1,55 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use json_validate_core::{
+    data_commitment, leaf_hash, merkle_root, raw_commitment, validity_bitmap, BatchInput,
+    BatchJournal, CompiledSchema, BATCH_JOURNAL_VERSION,
+};
+use risc0_zkvm::guest::env;
+
+// Validates N documents against one schema, compiling the schema only once.
+fn main() {
+    let input: BatchInput = env::read();
+
+    // Compile the schema, panicking with the same error as check_schema on a bad one.
+    let compiled_schema =
+        CompiledSchema::parse(&input.schema, input.draft).unwrap_or_else(|err| panic!("{err}"));
+
+    let mut valid = Vec::with_capacity(input.documents.len());
+    let mut leaves = Vec::with_capacity(input.documents.len());
+    for datastr in &input.documents {
+        // A record that is not JSON cannot conform; it must not abort the whole batch. Its leaf
+        // then covers the raw bytes, as record_commitment does on the host.
+        let (data_hash, conforms) = match serde_json::from_str::<serde_json::Value>(datastr) {
+            Ok(data) => (
+                data_commitment(&data, None),
+                compiled_schema.validate(&data).is_empty(),
+            ),
+            Err(_) => (raw_commitment(datastr.as_bytes(), None), false),
+        };
+        leaves.push(leaf_hash(&data_hash, conforms));
+        valid.push(conforms);
+    }
+
+    let journal = BatchJournal {
+        version: BATCH_JOURNAL_VERSION,
+        schema_hash: compiled_schema.schema_hash(),
+        draft: compiled_schema.draft(),
+        count: input.documents.len() as u32,
+        validity: validity_bitmap(&valid),
+        root: merkle_root(&leaves),
+    };
+
+    env::commit_slice(&journal.abi_encode());
+}
@@ -0,0 +1,67 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::{BufRead, BufReader};
+
+use json_validate_core::{
+    ndjson_record, CompiledSchema, StreamInput, StreamJournal, STREAM_JOURNAL_VERSION,
+};
+use risc0_zkvm::guest::env;
+use sha2::{Digest, Sha256};
+
+// Validates an NDJSON stream read from stdin, holding only one record in memory at a time.
+fn main() {
+    let input: StreamInput = env::read();
+
+    // Compile the schema, panicking with the same error as check_schema on a bad one.
+    let compiled_schema =
+        CompiledSchema::parse(&input.schema, input.draft).unwrap_or_else(|err| panic!("{err}"));
+
+    let mut stream = BufReader::new(env::stdin());
+    let mut stream_hash = Sha256::new();
+    let mut line = Vec::new();
+    let mut count = 0u64;
+    let mut valid_count = 0u64;
+    loop {
+        line.clear();
+        if stream.read_until(b'\n', &mut line).unwrap() == 0 {
+            break;
+        }
+        stream_hash.update(&line);
+
+        let Some(record) = ndjson_record(&line) else {
+            continue;
+        };
+        count += 1;
+        // A record that is not JSON cannot conform; it must not abort the whole stream.
+        let conforms = match serde_json::from_slice::<serde_json::Value>(record) {
+            Ok(data) => compiled_schema.validate(&data).is_empty(),
+            Err(_) => false,
+        };
+        if conforms {
+            valid_count += 1;
+        }
+    }
+
+    let journal = StreamJournal {
+        version: STREAM_JOURNAL_VERSION,
+        schema_hash: compiled_schema.schema_hash(),
+        draft: compiled_schema.draft(),
+        count,
+        valid_count,
+        stream_hash: stream_hash.finalize().into(),
+    };
+
+    env::commit_slice(&journal.abi_encode());
+}
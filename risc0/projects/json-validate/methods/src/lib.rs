@@ -12,4 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-include!(concat!(env!("OUT_DIR"), "/methods.rs"));
\ No newline at end of file
+include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+
+/// The schema embedded in [CHECK_FIXED_SCHEMA_ELF].
+pub const CHECK_FIXED_SCHEMA_SCHEMA: &str = include_str!(env!("CHECK_FIXED_SCHEMA_PATH"));
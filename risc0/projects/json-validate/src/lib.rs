@@ -0,0 +1,384 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Proving and executing the json-validate guests from Rust.
+//!
+//! [prove_schema] and [execute_schema] run the check_schema guest, or the check_fixed_schema
+//! guest when no schema is given, and return their results without printing or writing
+//! anything. Call [SchemaProof::write] to store a proof the way `json-validate prove` does.
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Result};
+use json_validate_core::{
+    CompiledSchema, FixedSchemaInput, Journal, Predicate, SchemaDraft, SchemaInput, SALT_LEN,
+};
+use json_validate_methods::{
+    CHECK_FIXED_SCHEMA_ELF, CHECK_FIXED_SCHEMA_ID, CHECK_SCHEMA_ELF, CHECK_SCHEMA_ID,
+};
+use receipt_seal::encode_seal;
+use receipt_store::Index;
+use risc0_zkvm::{
+    default_executor, default_prover, sha::Digest, ExecutorEnv, ExitCode, ProverOpts, Receipt,
+    VerifierContext,
+};
+
+/// The kind of receipt to produce.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ReceiptKind {
+    Composite,
+    Succinct,
+    #[default]
+    Groth16,
+}
+
+impl ReceiptKind {
+    /// The name used in receipt file names and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            ReceiptKind::Composite => "composite",
+            ReceiptKind::Succinct => "succinct",
+            ReceiptKind::Groth16 => "groth16",
+        }
+    }
+
+    /// Prover options producing this kind of receipt.
+    pub fn prover_opts(self) -> ProverOpts {
+        match self {
+            ReceiptKind::Composite => ProverOpts::composite(),
+            ReceiptKind::Succinct => ProverOpts::succinct(),
+            ReceiptKind::Groth16 => ProverOpts::groth16(),
+        }
+    }
+}
+
+impl fmt::Display for ReceiptKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ReceiptKind {
+    type Err = anyhow::Error;
+
+    /// Parse the names returned by [ReceiptKind::name].
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "composite" => Ok(ReceiptKind::Composite),
+            "succinct" => Ok(ReceiptKind::Succinct),
+            "groth16" => Ok(ReceiptKind::Groth16),
+            _ => bail!("unknown receipt kind {s}: expected composite, succinct or groth16"),
+        }
+    }
+}
+
+/// Options for [prove_schema] and [execute_schema].
+#[derive(Clone, Debug, Default)]
+pub struct SchemaOptions {
+    /// Draft to validate under, overriding the schema's `$schema` keyword. Must be `None` for
+    /// the fixed schema, whose draft is set by its own `$schema`.
+    pub draft: Option<SchemaDraft>,
+
+    /// JSON pointers to fields whose values are disclosed in the journal.
+    pub disclose: Vec<String>,
+
+    /// Predicate evaluated over the data without revealing it.
+    pub predicate: Option<Predicate>,
+
+    /// Salt blinding the committed data hash.
+    pub salt: Option<[u8; SALT_LEN]>,
+
+    /// The kind of receipt [prove_schema] produces.
+    pub receipt_kind: ReceiptKind,
+}
+
+/// A verified receipt from [prove_schema].
+#[derive(Clone, Debug)]
+pub struct SchemaProof {
+    /// The receipt, verified against `image_id`.
+    pub receipt: Receipt,
+
+    /// The decoded journal. `journal.valid` is false if the document does not conform.
+    pub journal: Journal,
+
+    /// The on-chain seal, see [receipt_seal]. `None` for composite and succinct receipts.
+    pub seal: Option<Vec<u8>>,
+
+    /// The image ID of the guest that produced the receipt.
+    pub image_id: Digest,
+
+    /// The kind of `receipt`, as requested in [SchemaOptions].
+    pub receipt_kind: ReceiptKind,
+}
+
+/// Where [SchemaProof::write] and [write_receipt] store their files.
+#[derive(Clone, Debug)]
+pub struct OutputOptions {
+    /// Directory the hex-encoded seal, journal and image ID files are written to.
+    pub out_dir: PathBuf,
+
+    /// Receipt file. Defaults to `<out_dir>/<name>_<receipt kind>.json`.
+    pub receipt: Option<PathBuf>,
+
+    /// Whether to write `seal.dat`, `journal.dat` and `image_id.dat`.
+    pub dat: bool,
+}
+
+impl SchemaProof {
+    /// The receipt's [receipt_store] index: its image ID and the hashes the journal commits to.
+    pub fn index(&self) -> Index {
+        let label = if self.image_id == CHECK_FIXED_SCHEMA_ID.into() {
+            "check_fixed_schema"
+        } else {
+            "check_schema"
+        };
+        Index {
+            data_hash: Some(self.journal.data_hash),
+            schema_hash: Some(self.journal.schema_hash),
+            ..Index::new(self.image_id, label)
+        }
+    }
+
+    /// Write the receipt and, if enabled, the `.dat` files. Returns the receipt path.
+    pub fn write(&self, output: &OutputOptions) -> Result<PathBuf> {
+        write_receipt(
+            &self.receipt,
+            self.image_id,
+            "receipt",
+            self.receipt_kind,
+            output,
+        )
+    }
+}
+
+/// The outcome of running a guest without proving it.
+#[derive(Clone, Debug)]
+pub struct ExecutionReport {
+    /// The decoded journal the guest committed.
+    pub journal: Journal,
+
+    /// How the guest exited.
+    pub exit_code: ExitCode,
+
+    /// Number of continuation segments.
+    pub segments: usize,
+
+    /// User cycles across all segments, without continuation or padding overhead.
+    pub user_cycles: u64,
+}
+
+/// Prove that `data` does or does not conform to `schema`.
+///
+/// Without a schema the check_fixed_schema guest validates against the schema embedded at build
+/// time. The receipt is verified before it is returned.
+pub fn prove_schema(data: &str, schema: Option<&str>, opts: &SchemaOptions) -> Result<SchemaProof> {
+    let (env, elf, image_id) = guest_input(data, schema, opts)?;
+    let receipt = prove(env, elf, image_id, opts.receipt_kind)?;
+    let journal = Journal::abi_decode(&receipt.journal.bytes)?;
+    Ok(SchemaProof {
+        seal: encode_seal(&receipt.inner).ok(),
+        receipt,
+        journal,
+        image_id,
+        receipt_kind: opts.receipt_kind,
+    })
+}
+
+/// Run the guest [prove_schema] would prove, without proving it.
+///
+/// `opts.receipt_kind` is ignored.
+pub fn execute_schema(
+    data: &str,
+    schema: Option<&str>,
+    opts: &SchemaOptions,
+) -> Result<ExecutionReport> {
+    let (env, elf, _) = guest_input(data, schema, opts)?;
+    let session = default_executor().execute(env, elf)?;
+    Ok(ExecutionReport {
+        journal: Journal::abi_decode(&session.journal.bytes)?,
+        exit_code: session.exit_code,
+        segments: session.segments.len(),
+        user_cycles: session.cycles(),
+    })
+}
+
+/// Prove `elf` with the input in `env` and verify the receipt against `image_id`.
+pub fn prove(
+    env: ExecutorEnv<'_>,
+    elf: &[u8],
+    image_id: impl Into<Digest>,
+    receipt_kind: ReceiptKind,
+) -> Result<Receipt> {
+    // Obtain the default prover.
+    let prover = default_prover();
+
+    // Produce a receipt by proving the specified ELF binary.
+    let receipt = prover
+        .prove_with_ctx(
+            env,
+            &VerifierContext::default(),
+            elf,
+            &receipt_kind.prover_opts(),
+        )?
+        .receipt;
+
+    receipt.verify(image_id)?;
+    Ok(receipt)
+}
+
+/// Writes the receipt, and if enabled the `.dat` files, to the output directory. Returns the
+/// receipt path.
+///
+/// `seal.dat` is only written for receipts that can be verified on-chain.
+pub fn write_receipt(
+    receipt: &Receipt,
+    image_id: Digest,
+    name: &str,
+    receipt_kind: ReceiptKind,
+    output: &OutputOptions,
+) -> Result<PathBuf> {
+    fs::create_dir_all(&output.out_dir)
+        .with_context(|| format!("creating {}", output.out_dir.display()))?;
+
+    if output.dat {
+        if let Ok(seal) = encode_seal(&receipt.inner) {
+            write_dat(&output.out_dir, "seal.dat", &seal)?;
+        }
+        write_dat(&output.out_dir, "journal.dat", &receipt.journal.bytes)?;
+        write_dat(&output.out_dir, "image_id.dat", image_id.as_bytes())?;
+    }
+
+    // Dump receipt using serde
+    let receipt_path = output.receipt.clone().unwrap_or_else(|| {
+        output
+            .out_dir
+            .join(format!("{name}_{}.json", receipt_kind.name()))
+    });
+    fs::write(&receipt_path, serde_json::to_string_pretty(receipt)?)
+        .with_context(|| format!("writing {}", receipt_path.display()))?;
+    Ok(receipt_path)
+}
+
+fn write_dat(out_dir: &Path, name: &str, bytes: &[u8]) -> Result<()> {
+    let path = out_dir.join(name);
+    fs::write(&path, hex::encode(bytes)).with_context(|| format!("writing {}", path.display()))
+}
+
+/// The draft `schema` is validated under.
+///
+/// Fails with the guests' own [SchemaError](json_validate_core::SchemaError) on a schema they
+/// would panic on: one that is not JSON, names no known draft in `$schema`, or does not compile.
+pub fn select_draft(schema: &str, requested: Option<SchemaDraft>) -> Result<SchemaDraft> {
+    Ok(CompiledSchema::parse(schema, requested)?.draft())
+}
+
+/// The input and image of the guest that checks `data` against `schema`.
+fn guest_input(
+    data: &str,
+    schema: Option<&str>,
+    opts: &SchemaOptions,
+) -> Result<(ExecutorEnv<'static>, &'static [u8], Digest)> {
+    if let Some(pointer) = opts
+        .disclose
+        .iter()
+        .find(|pointer| !pointer.is_empty() && !pointer.starts_with('/'))
+    {
+        bail!("invalid JSON pointer '{pointer}', pointers must start with '/'");
+    }
+
+    let mut builder = ExecutorEnv::builder();
+    let predicate = opts.predicate.as_ref().map(Predicate::to_string);
+    let (elf, image_id) = match schema {
+        Some(schema) => {
+            // The guest panics on a schema it cannot compile, so reject it before proving.
+            select_draft(schema, opts.draft)?;
+            builder.write(&SchemaInput {
+                data: data.to_string(),
+                schema: schema.to_string(),
+                draft: opts.draft,
+                disclose: opts.disclose.clone(),
+                predicate,
+                salt: opts.salt,
+            })?;
+            (CHECK_SCHEMA_ELF, CHECK_SCHEMA_ID)
+        }
+        None => {
+            // The embedded schema's draft is fixed by its own `$schema` keyword.
+            if opts.draft.is_some() {
+                bail!("a draft cannot be given for the fixed schema");
+            }
+            builder.write(&FixedSchemaInput {
+                data: data.to_string(),
+                disclose: opts.disclose.clone(),
+                predicate,
+                salt: opts.salt,
+            })?;
+            (CHECK_FIXED_SCHEMA_ELF, CHECK_FIXED_SCHEMA_ID)
+        }
+    };
+    Ok((builder.build()?, elf, image_id.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn success_case() {
+        let data = include_str!("../res/data.json");
+        let schema = include_str!("../res/schema.json");
+
+        let report = execute_schema(data, Some(schema), &SchemaOptions::default()).unwrap();
+        assert!(
+            report.journal.valid,
+            "The input data is not satisfy the schema"
+        );
+    }
+
+    #[test]
+    fn fail_case() {
+        let data = include_str!("../res/data_failcase.json");
+        let schema = include_str!("../res/schema.json");
+
+        let report = execute_schema(data, Some(schema), &SchemaOptions::default()).unwrap();
+        assert!(
+            !report.journal.valid,
+            "The input data is satisfy the schema"
+        );
+    }
+
+    #[test]
+    fn invalid_options() {
+        let opts = SchemaOptions {
+            disclose: vec!["name".into()],
+            ..Default::default()
+        };
+        assert!(execute_schema("{}", Some("{}"), &opts).is_err());
+
+        let opts = SchemaOptions {
+            draft: Some(SchemaDraft::Draft7),
+            ..Default::default()
+        };
+        assert!(execute_schema("{}", None, &opts).is_err());
+
+        // Rejected on the host, where the guest would panic.
+        let opts = SchemaOptions::default();
+        assert!(execute_schema("{}", Some("{\"type\""), &opts).is_err());
+    }
+}
@@ -11,243 +11,680 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-// use serde_json::json;
-// use jsonschema::{Draft, JSONSchema};
-// use json_validate_core::Outputs;
-// use alloy::sol_types::SolValue;
 
-use json_validate_methods::{CHECK_SCHEMA_ELF,CHECK_SCHEMA_ID};
-// use risc0_zkvm::{default_prover, ExecutorEnv};
-use risc0_zkvm::{compute_image_id,default_prover, ExecutorEnv, ProverOpts, VerifierContext, InnerReceipt, sha::Digestible};
-use std::fs::File;
-use std::io::Write;
-use anyhow::{Result, bail, Context};
-use alloy_sol_types::SolValue;
-use std::env;
-use std::fs;
-// use std::time::Instant;
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand};
+use json_validate::{
+    execute_schema, prove, prove_schema, select_draft, write_receipt, OutputOptions, ReceiptKind,
+    SchemaOptions,
+};
+use json_validate_core::{
+    data_commitment, leaf_hash, record_commitment, schema_commitment, BatchInput, BatchJournal,
+    InclusionProof, Journal, Predicate, SchemaDraft, StreamInput, StreamJournal, SALT_LEN,
+};
+use json_validate_methods::{
+    CHECK_FIXED_SCHEMA_ELF, CHECK_FIXED_SCHEMA_ID, CHECK_FIXED_SCHEMA_SCHEMA,
+    CHECK_SCHEMA_BATCH_ELF, CHECK_SCHEMA_BATCH_ID, CHECK_SCHEMA_ID, CHECK_SCHEMA_STREAM_ELF,
+    CHECK_SCHEMA_STREAM_ID,
+};
+use receipt_seal::encode_seal;
+use receipt_store::{Index, Query, ReceiptStore};
+use risc0_zkvm::{
+    compute_image_id, default_executor, sha::Digestible, ExecutorEnv, InnerReceipt, Receipt,
+};
+
+/// Proves that a JSON document conforms to a JSON schema using the RISC Zero zkVM.
+#[derive(Parser)]
+#[command(about, version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
 
-fn main() {
+#[derive(Subcommand)]
+enum Command {
+    /// Run the guest without proving and report the cycle count.
+    Execute {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+
+    /// Prove that the data conforms to the schema.
+    Prove {
+        #[command(flatten)]
+        input: InputArgs,
+
+        /// The kind of receipt to produce.
+        #[arg(long, value_enum, default_value_t = ReceiptKind::Groth16)]
+        receipt_kind: ReceiptKind,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Verify a receipt produced by `prove` against the check_schema image ID.
+    Verify {
+        /// Receipt file, as written by `prove`.
+        receipt: PathBuf,
+
+        /// Verify against the check_fixed_schema image ID instead.
+        #[arg(long, conflicts_with = "ndjson")]
+        fixed_schema: bool,
+
+        /// Verify against the check_schema_stream image ID instead.
+        #[arg(long)]
+        ndjson: bool,
+    },
+
+    /// Print the schema embedded in the check_fixed_schema guest, its hash and its image ID.
+    FixedSchema,
+
+    /// Prove many documents against one schema with a single receipt.
+    ///
+    /// Also writes `batch_proofs.json`, one Merkle inclusion proof per document.
+    Batch {
+        /// JSON schema every document is validated against.
+        #[arg(long)]
+        schema: PathBuf,
+
+        /// JSON documents to validate, in batch order.
+        #[arg(required = true)]
+        data: Vec<PathBuf>,
+
+        /// JSON Schema draft to validate under, overriding the schema's `$schema` keyword.
+        #[arg(long)]
+        draft: Option<SchemaDraft>,
+
+        /// The kind of receipt to produce.
+        #[arg(long, value_enum, default_value_t = ReceiptKind::Groth16)]
+        receipt_kind: ReceiptKind,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Check that a document is covered by a batch receipt and report its validity.
+    VerifyRecord {
+        /// Receipt file, as written by `batch`.
+        receipt: PathBuf,
+
+        /// Inclusion proofs file, as written by `batch`.
+        proofs: PathBuf,
+
+        /// The JSON document to look up.
+        data: PathBuf,
+    },
+
+    /// Print the data hash a receipt commits to for a document.
+    ///
+    /// Holders of the salt use this to match a document with the `dataHash` of a salted receipt.
+    Commitment {
+        /// The JSON document.
+        data: PathBuf,
+
+        /// The 32-byte hex salt the document was proven with.
+        #[arg(long, value_parser = parse_salt)]
+        salt: Option<[u8; SALT_LEN]>,
+    },
+
+    /// Print the journal, seal and claim of a receipt.
+    Inspect {
+        /// Receipt file, as written by `prove`.
+        receipt: PathBuf,
+    },
+
+    /// List the receipts in a receipt store, optionally re-verifying them.
+    List {
+        /// The receipt store, as given to `--store`.
+        store: PathBuf,
+
+        /// Only list receipts for this image ID, hex-encoded.
+        #[arg(long, value_parser = parse_hash)]
+        image_id: Option<[u8; 32]>,
+
+        /// Only list receipts committing to this data hash, hex-encoded.
+        #[arg(long, value_parser = parse_hash)]
+        data_hash: Option<[u8; 32]>,
+
+        /// Only list receipts committing to this schema hash, hex-encoded.
+        #[arg(long, value_parser = parse_hash)]
+        schema_hash: Option<[u8; 32]>,
+
+        /// Verify every listed receipt against its image ID.
+        #[arg(long)]
+        verify: bool,
+    },
+}
 
-    let args: Vec<String> = env::args().collect();
+#[derive(Args)]
+struct InputArgs {
+    /// JSON document to validate.
+    data: PathBuf,
+
+    /// JSON schema the document is validated against.
+    ///
+    /// When omitted, the document is checked by the check_fixed_schema guest against the
+    /// schema embedded at build time.
+    schema: Option<PathBuf>,
+
+    /// JSON Schema draft to validate under: 4, 6, 7, 2019-09 or 2020-12.
+    ///
+    /// Overrides the schema's `$schema` keyword. Defaults to draft 7 when neither is present.
+    #[arg(long)]
+    draft: Option<SchemaDraft>,
+
+    /// Treat the data file as newline-delimited JSON with one record per line.
+    ///
+    /// Records are streamed to the check_schema_stream guest through stdin instead of being
+    /// loaded into memory. Requires a schema argument.
+    #[arg(long)]
+    ndjson: bool,
+
+    /// JSON pointer to a field whose value is revealed in the journal, e.g. `/address/state`.
+    ///
+    /// May be repeated. Only the listed fields are disclosed.
+    #[arg(long = "disclose", value_name = "POINTER")]
+    disclose: Vec<String>,
+
+    /// Predicate evaluated over the data without revealing it, e.g. `/age >= 18`.
+    ///
+    /// Clauses compare a JSON pointer with a JSON literal using `==`, `!=`, `<`, `<=`, `>`, `>=`
+    /// or `in`, and are joined with `&&`.
+    #[arg(long)]
+    predicate: Option<Predicate>,
+
+    /// 32-byte hex salt that blinds the committed data hash.
+    ///
+    /// Use a fresh random salt, e.g. from `openssl rand -hex 32`, and keep it with the data:
+    /// it is needed to reproduce the commitment with the `commitment` subcommand.
+    #[arg(long, value_parser = parse_salt)]
+    salt: Option<[u8; SALT_LEN]>,
+}
 
-    let data_filename = &args[1];
-    let schema_filename = &args[2];
+#[derive(Args)]
+struct OutputArgs {
+    /// Directory the hex-encoded seal, journal and image ID files are written to.
+    #[arg(long, default_value = "./res")]
+    out_dir: PathBuf,
+
+    /// Receipt output file. Defaults to `<OUT_DIR>/receipt_<RECEIPT_KIND>.json`, or
+    /// `<OUT_DIR>/receipt_batch_<RECEIPT_KIND>.json` for `batch`.
+    #[arg(long)]
+    receipt: Option<PathBuf>,
+
+    /// Only write the receipt, skip the `.dat` files.
+    #[arg(long)]
+    no_dat: bool,
+
+    /// Also add the receipt to the receipt store in DIR, indexed by its image ID and the hashes
+    /// its journal commits to. Unlike the receipt file, stored receipts are never overwritten.
+    #[arg(long, value_name = "DIR")]
+    store: Option<PathBuf>,
+}
 
-    // let data = "{\"name1\": \"John Doe\",\"age\": 23}";
-    // let data = include_str!("../res/data_complex_obj.json");
-    // let schema = include_str!("../res/schema_complex_obj.json");
+impl OutputArgs {
+    fn options(&self) -> OutputOptions {
+        OutputOptions {
+            out_dir: self.out_dir.clone(),
+            receipt: self.receipt.clone(),
+            dat: !self.no_dat,
+        }
+    }
+}
 
-    // let data = include_str!("../res/data_array.json");
-    // let schema = include_str!("../res/schema_array.json");
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Execute { input } => execute(&input),
+        Command::Prove {
+            input,
+            receipt_kind,
+            output,
+        } => check_schema(&input, receipt_kind, &output),
+        Command::Verify {
+            receipt,
+            fixed_schema,
+            ndjson,
+        } => {
+            let image_id = if fixed_schema {
+                CHECK_FIXED_SCHEMA_ID
+            } else if ndjson {
+                CHECK_SCHEMA_STREAM_ID
+            } else {
+                CHECK_SCHEMA_ID
+            };
+            verify_receipt(&read_receipt(&receipt)?, image_id)
+        }
+        Command::Batch {
+            schema,
+            data,
+            draft,
+            receipt_kind,
+            output,
+        } => check_schema_batch(&schema, &data, draft, receipt_kind, &output),
+        Command::VerifyRecord {
+            receipt,
+            proofs,
+            data,
+        } => verify_record(&read_receipt(&receipt)?, &proofs, &data),
+        Command::Commitment { data, salt } => {
+            let data = fs::read_to_string(&data)
+                .with_context(|| format!("reading data file {}", data.display()))?;
+            let data: serde_json::Value = serde_json::from_str(&data).context("parsing data")?;
+            println!("{}", hex::encode(data_commitment(&data, salt.as_ref())));
+            Ok(())
+        }
+        Command::Inspect { receipt } => inspect_receipt(&read_receipt(&receipt)?),
+        Command::List {
+            store,
+            image_id,
+            data_hash,
+            schema_hash,
+            verify,
+        } => {
+            let query = Query {
+                image_id,
+                data_hash,
+                schema_hash,
+            };
+            list_receipts(&store, &query, verify)
+        }
+        Command::FixedSchema => {
+            println!(
+                "image ID: {}",
+                hex::encode(compute_image_id(CHECK_FIXED_SCHEMA_ELF)?)
+            );
+            let schema: serde_json::Value = serde_json::from_str(CHECK_FIXED_SCHEMA_SCHEMA)?;
+            println!("schema hash: {}", hex::encode(schema_commitment(&schema)));
+            println!("{CHECK_FIXED_SCHEMA_SCHEMA}");
+            Ok(())
+        }
+    }
+}
 
-    // let data = include_str!("../res/data.json");
-    // let schema = include_str!("..res/schema.json");
-    // let args: Vec<String> = env::args().collect();
-    // let filename = &args[1];
+impl InputArgs {
+    /// Library options for the document, rejecting combinations the guests do not support.
+    fn schema_options(&self, receipt_kind: ReceiptKind) -> Result<SchemaOptions> {
+        // The embedded schema's draft is fixed by its own `$schema` keyword.
+        if self.schema.is_none() && self.draft.is_some() {
+            bail!("--draft requires a schema argument");
+        }
+        Ok(SchemaOptions {
+            draft: self.draft,
+            disclose: self.disclose.clone(),
+            predicate: self.predicate.clone(),
+            salt: self.salt,
+            receipt_kind,
+        })
+    }
 
-    if data_filename.len() == 0 || schema_filename.len() == 0{
-        println!("Error NO input file. Usage: cargo run <data.json> <schema.json>");
+    /// Input for the check_schema_stream guest, which reads the records from stdin.
+    fn stream_env(&self) -> Result<ExecutorEnv<'static>> {
+        let Some(schema) = self.read_schema()? else {
+            bail!("--ndjson requires a schema argument");
+        };
+        // The guest panics on a schema it cannot compile, so reject it before proving.
+        select_draft(&schema, self.draft)?;
+        if !self.disclose.is_empty() || self.predicate.is_some() || self.salt.is_some() {
+            bail!("--disclose, --predicate and --salt cannot be combined with --ndjson");
+        }
+        let stream = File::open(&self.data)
+            .with_context(|| format!("opening data file {}", self.data.display()))?;
+        ExecutorEnv::builder()
+            .write(&StreamInput {
+                schema,
+                draft: self.draft,
+            })?
+            .stdin(stream)
+            .build()
     }
-    // let data = include_str!(filename);
-    // println!("input {}", filename);
 
-    let data = fs::read_to_string(data_filename).expect("Should have been able to read the file");
+    fn read_data(&self) -> Result<String> {
+        fs::read_to_string(&self.data)
+            .with_context(|| format!("reading data file {}", self.data.display()))
+    }
 
-    let schema = fs::read_to_string(schema_filename).expect("Should have been able to read the file");
-    // let outputs = check_schema(data, schema);
-    // println!();
-    // println!("validate schema result {}", outputs);
+    fn read_schema(&self) -> Result<Option<String>> {
+        self.schema
+            .as_ref()
+            .map(|path| {
+                fs::read_to_string(path)
+                    .with_context(|| format!("reading schema file {}", path.display()))
+            })
+            .transpose()
+    }
+}
 
-    // let _ = benchmark_prove(data, schema);
-    let _ = check_schema(&data, &schema);
+fn parse_salt(hex_salt: &str) -> Result<[u8; SALT_LEN]> {
+    let mut salt = [0u8; SALT_LEN];
+    hex::decode_to_slice(hex_salt, &mut salt)
+        .with_context(|| format!("salt must be {SALT_LEN} hex-encoded bytes"))?;
+    Ok(salt)
 }
 
+fn parse_hash(hex_hash: &str) -> Result<[u8; 32]> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(hex_hash.trim_start_matches("0x"), &mut hash)
+        .context("expected 32 hex-encoded bytes")?;
+    Ok(hash)
+}
 
-fn check_schema(data: &str, schema: &str) -> Result<()> {
-    let input = (data, schema);
-    println!("data {}", data);
-    println!("schema {}", schema);
+fn execute(input: &InputArgs) -> Result<()> {
+    if input.ndjson {
+        let session = default_executor().execute(input.stream_env()?, CHECK_SCHEMA_STREAM_ELF)?;
+        println!("exit code: {:?}", session.exit_code);
+        println!("segments: {}", session.segments.len());
+        println!("user cycles: {}", session.cycles());
+        print_stream_journal(&StreamJournal::abi_decode(&session.journal.bytes)?);
+        return Ok(());
+    }
 
-    let env = ExecutorEnv::builder()
-        .write(&input)
-        .unwrap()
-        .build()
-        .unwrap();
+    let report = execute_schema(
+        &input.read_data()?,
+        input.read_schema()?.as_deref(),
+        &input.schema_options(ReceiptKind::default())?,
+    )?;
+    println!("exit code: {:?}", report.exit_code);
+    println!("segments: {}", report.segments);
+    println!("user cycles: {}", report.user_cycles);
+    print_journal(&report.journal);
+    Ok(())
+}
 
-    // // Obtain the default prover.
-    let prover = default_prover();
+fn check_schema(input: &InputArgs, receipt_kind: ReceiptKind, output: &OutputArgs) -> Result<()> {
+    if input.ndjson {
+        let receipt = prove(
+            input.stream_env()?,
+            CHECK_SCHEMA_STREAM_ELF,
+            CHECK_SCHEMA_STREAM_ID,
+            receipt_kind,
+        )?;
+        let journal = StreamJournal::abi_decode(&receipt.journal.bytes)?;
+        print_stream_journal(&journal);
+        // The stream hash covers the raw NDJSON bytes, not a data commitment, so it is not
+        // indexed as a data hash.
+        let index = Index {
+            schema_hash: Some(journal.schema_hash),
+            ..Index::new(CHECK_SCHEMA_STREAM_ID, "check_schema_stream")
+        };
+        return write_receipt_files(&receipt, index, "receipt", receipt_kind, output);
+    }
 
-    // // Produce a receipt by proving the specified ELF binary.
-    // let receipt = prover.prove(env, CHECK_SCHEMA_ELF).unwrap().receipt;
+    let proof = prove_schema(
+        &input.read_data()?,
+        input.read_schema()?.as_deref(),
+        &input.schema_options(receipt_kind)?,
+    )?;
+    print_journal(&proof.journal);
+    write_receipt_files(
+        &proof.receipt,
+        proof.index(),
+        "receipt",
+        receipt_kind,
+        output,
+    )
+}
 
+fn check_schema_batch(
+    schema_path: &Path,
+    data_paths: &[PathBuf],
+    draft: Option<SchemaDraft>,
+    receipt_kind: ReceiptKind,
+    output: &OutputArgs,
+) -> Result<()> {
+    let schema = fs::read_to_string(schema_path)
+        .with_context(|| format!("reading schema file {}", schema_path.display()))?;
+    select_draft(&schema, draft)?;
+    let documents = data_paths
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path)
+                .with_context(|| format!("reading data file {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
 
-    let receipt = prover.prove_with_ctx(
+    let env = ExecutorEnv::builder()
+        .write(&BatchInput {
+            schema,
+            documents: documents.clone(),
+            draft,
+        })?
+        .build()?;
+    let receipt = prove(
         env,
-        &VerifierContext::default(),
-        CHECK_SCHEMA_ELF,
-        &ProverOpts::groth16(),
-    )?
-    .receipt;
-
-    receipt.verify(CHECK_SCHEMA_ID).unwrap();
-
-    // Encode the seal with the selector.
-    let seal = encode_seal(&receipt)?;
-
-    // let seal_hex_string = vec_to_hex_string(&seal);
-    println!("seal hex_string: {}", hex::encode(seal.clone()));
-
-    let mut file_seal = File::create("./res/seal.dat").expect("failed to create file");
-    file_seal.write_all(hex::encode(seal.clone()).as_bytes()).expect("failed to write");
-        
-    // Extract the journal from the receipt.
-    let journal = receipt.journal.bytes.clone();
-
-    // Decode Journal: Upon receiving the proof, the application decodes the journal to extract
-    // the verified number. This ensures that the number being submitted to the blockchain matches
-    // the number that was verified off-chain.
-
-
-    println!("journal: {}", hex::encode(journal.clone()));
-
-    let mut file_journal = File::create("./res/journal.dat").expect("failed to create file");
-    file_journal.write_all(hex::encode(journal.clone()).as_bytes()).expect("failed to write");
-
-    let x = Vec::<u8>::abi_decode(&journal, true).context("decoding journal data")?;
-    
-    println!("journal abi_decode: {}", hex::encode(x.clone()));
-
-    let mut file_journal_abi = File::create("./res/journal_abi.dat").expect("failed to create file");
-    file_journal_abi.write_all(hex::encode(x.clone()).as_bytes()).expect("failed to write");
-
-    // Compute the Image ID
-    let image_id = hex::encode(compute_image_id(CHECK_SCHEMA_ELF)?);
+        CHECK_SCHEMA_BATCH_ELF,
+        CHECK_SCHEMA_BATCH_ID,
+        receipt_kind,
+    )?;
+    let journal = BatchJournal::abi_decode(&receipt.journal.bytes)?;
+    print_batch_journal(&journal);
+
+    // The leaves are rebuilt from the committed validity bits, so any mismatch with the
+    // documents read above shows up as a root that the proofs do not verify against.
+    let data_hashes: Vec<[u8; 32]> = documents
+        .iter()
+        .map(|document| record_commitment(document.as_bytes()))
+        .collect();
+    let leaves: Vec<_> = (0..journal.count)
+        .map(|index| leaf_hash(&data_hashes[index as usize], journal.is_valid(index)))
+        .collect();
+    let proofs = (0..journal.count)
+        .map(|index| {
+            InclusionProof::new(
+                &leaves,
+                index,
+                data_hashes[index as usize],
+                journal.is_valid(index),
+            )
+            .filter(|proof| proof.verify(&journal))
+            .with_context(|| {
+                format!(
+                    "no inclusion proof for {}",
+                    data_paths[index as usize].display()
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let index = Index {
+        schema_hash: Some(journal.schema_hash),
+        ..Index::new(CHECK_SCHEMA_BATCH_ID, "check_schema_batch")
+    };
+    write_receipt_files(&receipt, index, "receipt_batch", receipt_kind, output)?;
+    let proofs_path = output.out_dir.join("batch_proofs.json");
+    fs::write(&proofs_path, serde_json::to_string_pretty(&proofs)?)
+        .with_context(|| format!("writing {}", proofs_path.display()))?;
+    println!("inclusion proofs written to {}", proofs_path.display());
 
-    println!("Image ID: {}", image_id.clone());
+    Ok(())
+}
 
-    
-    let mut file_image = File::create("./res/image_id.dat").expect("failed to create file");
-    file_image.write_all(image_id.clone().as_bytes()).expect("failed to write");
+/// Writes the receipt, and unless disabled the `.dat` files, and prints what was written.
+///
+/// With `--store` the receipt is also added to the receipt store under `index`.
+fn write_receipt_files(
+    receipt: &Receipt,
+    index: Index,
+    name: &str,
+    receipt_kind: ReceiptKind,
+    output: &OutputArgs,
+) -> Result<()> {
+    let image_id = risc0_zkvm::sha::Digest::from(index.image_id);
+    let receipt_path = write_receipt(receipt, image_id, name, receipt_kind, &output.options())?;
+
+    if !output.no_dat {
+        match encode_seal(&receipt.inner) {
+            Ok(seal) => println!("seal: {}", hex::encode(seal)),
+            Err(err) => println!("skipping seal.dat: {err}"),
+        }
+        println!("journal: {}", hex::encode(&receipt.journal.bytes));
+        println!("image ID: {}", hex::encode(image_id));
+    }
+    println!("receipt written to {}", receipt_path.display());
 
-    // Dump receipe using serde
-    let receipt_json = serde_json::to_string_pretty(&receipt).unwrap();
+    if let Some(dir) = &output.store {
+        let entry = ReceiptStore::open(dir)?.put(receipt, index)?;
+        println!("stored receipt {}", entry.id);
+    }
 
-    // Write the JSON string to a file
-    let mut file = File::create("./res/receipt_groth16.json").expect("failed to create file");
-    file.write_all(receipt_json.as_bytes()).expect("failed to write");
+    Ok(())
+}
 
-    // println!("Data written to file successfully.");
+fn read_receipt(path: &Path) -> Result<Receipt> {
+    let receipt_json =
+        fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    serde_json::from_str(&receipt_json).with_context(|| format!("parsing {}", path.display()))
+}
 
-    // receipt.journal.decode.unwrap();
+fn verify_receipt(receipt: &Receipt, image_id: [u32; 8]) -> Result<()> {
+    receipt
+        .verify(image_id)
+        .context("receipt verification failed")?;
+    println!("receipt OK");
     Ok(())
 }
 
-// fn vec_to_hex_string(vec: &[u8]) -> String { 
-//     let mut hex_string = String::from("0x"); 
-//     for byte in vec { 
-//         hex_string.push_str(&format!("{:02x}", byte)); 
-//     } 
-//     hex_string 
-// }
-
-pub fn encode_seal(receipt: &risc0_zkvm::Receipt) -> Result<Vec<u8>> {
-    let seal = match receipt.inner.clone() {
-        InnerReceipt::Fake(receipt) => {
-            let seal = receipt.claim.digest().as_bytes().to_vec();
-            let selector = &[0u8; 4];
-            // Create a new vector with the capacity to hold both selector and seal
-            let mut selector_seal = Vec::with_capacity(selector.len() + seal.len());
-            selector_seal.extend_from_slice(selector);
-            selector_seal.extend_from_slice(&seal);
-            selector_seal
+fn list_receipts(dir: &Path, query: &Query, verify: bool) -> Result<()> {
+    let store = ReceiptStore::open(dir)?;
+    let mut failed = 0;
+    for entry in store.list(query)? {
+        println!("{} {}", entry.id, entry.index.label);
+        println!("  image ID: {}", hex::encode(entry.index.image_id));
+        if let Some(data_hash) = entry.index.data_hash {
+            println!("  data hash: {}", hex::encode(data_hash));
         }
-        InnerReceipt::Groth16(receipt) => {
-            let selector = &receipt.verifier_parameters.as_bytes()[..4];
-            // Create a new vector with the capacity to hold both selector and seal
-            let mut selector_seal = Vec::with_capacity(selector.len() + receipt.seal.len());
-            selector_seal.extend_from_slice(selector);
-            selector_seal.extend_from_slice(receipt.seal.as_ref());
-            selector_seal
+        if let Some(schema_hash) = entry.index.schema_hash {
+            println!("  schema hash: {}", hex::encode(schema_hash));
         }
-        _ => bail!("Unsupported receipt type"),
-    };
-    Ok(seal)
+        println!("  receipt: {}", store.receipt_path(&entry.id).display());
+        if verify {
+            match store.verify(&entry.id) {
+                Ok(_) => println!("  receipt OK"),
+                Err(err) => {
+                    println!("  {err}");
+                    failed += 1;
+                }
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{failed} stored receipts failed verification");
+    }
+    Ok(())
 }
 
-/*
-fn benchmark_prove(data: &str, schema: &str) ->Result<()>{
-    // start benchmarks
-    const ITER: usize = 1;
-    let mut benches = Vec::new();
-    let mut benches_verify = Vec::new();
-
-    let input = (data, schema);
-    for _ in 0..ITER {
-        // Obtain the default prover.
-        let prover = default_prover();
-
-        let env = ExecutorEnv::builder()
-            .write(&input)
-            .unwrap()
-            .build()
-            .unwrap();
-
-        let before = Instant::now();
-        // Produce a receipt by proving the specified ELF binary.
-        // let receipt  = prover.prove(env, CHECK_SCHEMA_ELF).unwrap().receipt;
-        let receipt = prover.prove_with_ctx(
-            env,
-            &VerifierContext::default(),
-            CHECK_SCHEMA_ELF,
-            &ProverOpts::groth16(),
-        )?
-        .receipt;
-        // println!("\n###### Time: {:.2?}", before.elapsed());
-        benches.push(before.elapsed());
-
-        let before_verify = Instant::now();
-        receipt.verify(CHECK_SCHEMA_ID).unwrap();
-        benches_verify.push(before_verify.elapsed());
+fn verify_record(receipt: &Receipt, proofs_path: &Path, data_path: &Path) -> Result<()> {
+    receipt
+        .verify(CHECK_SCHEMA_BATCH_ID)
+        .context("receipt verification failed")?;
+    let journal = BatchJournal::abi_decode(&receipt.journal.bytes)?;
+
+    let proofs_json = fs::read_to_string(proofs_path)
+        .with_context(|| format!("reading {}", proofs_path.display()))?;
+    let proofs: Vec<InclusionProof> = serde_json::from_str(&proofs_json)
+        .with_context(|| format!("parsing {}", proofs_path.display()))?;
+    let data = fs::read(data_path)
+        .with_context(|| format!("reading data file {}", data_path.display()))?;
+    let data_hash = record_commitment(&data);
+
+    let Some(proof) = proofs.iter().find(|proof| proof.data_hash == data_hash) else {
+        bail!("{} is not in the batch", data_path.display());
+    };
+    if !proof.verify(&journal) {
+        bail!(
+            "inclusion proof for record {} does not match the receipt",
+            proof.index
+        );
     }
-
-    println!("\n-------- BENCHMARK VERIFY ---------");
-    for bench in benches_verify {
-        println!("{:.2?}", bench);
+    // The proof's validity bit is bound to the root, but check the bitmap agrees as well.
+    if proof.valid != journal.is_valid(proof.index) {
+        bail!(
+            "record {} validity does not match the journal bitmap",
+            proof.index
+        );
     }
-    println!("\n---------------------------");
-    
+    println!("record {} covered by receipt", proof.index);
+    println!("valid: {}", proof.valid);
+    Ok(())
+}
 
-    println!("\n-------- BENCHMARK ---------");
-    for bench in benches {
-        println!("{:.2?}", bench);
+fn inspect_receipt(receipt: &Receipt) -> Result<()> {
+    let kind = match &receipt.inner {
+        InnerReceipt::Composite(_) => "composite",
+        InnerReceipt::Succinct(_) => "succinct",
+        InnerReceipt::Groth16(_) => "groth16",
+        InnerReceipt::Fake(_) => "fake",
+        _ => "unknown",
+    };
+    println!("kind: {kind}");
+    println!("seal size: {} bytes", receipt.seal_size());
+    println!("claim digest: {}", receipt.claim()?.digest());
+    println!("journal: {}", hex::encode(&receipt.journal.bytes));
+    print_guest_journal(
+        receipt.claim()?.as_value()?.pre.digest(),
+        &receipt.journal.bytes,
+    )?;
+    if let Ok(seal) = encode_seal(&receipt.inner) {
+        println!("seal: {}", hex::encode(seal));
     }
-    println!("\n---------------------------");
-    // end benchmarks
-
     Ok(())
 }
-*/
-#[cfg(test)]
-mod tests {
-    use crate::check_schema;
-    #[test]
-    fn success_case() {
-        let data = include_str!("../res/data.json");
-        let schema = include_str!("../res/schema.json");
-
-        let outputs = check_schema(data, schema);
-        assert_eq!(
-            outputs.result, 1,
-            "The input data is not satisfy the schema"
-        );
+
+/// Decodes and prints a journal according to the guest that committed it.
+fn print_guest_journal(image_id: risc0_zkvm::sha::Digest, journal: &[u8]) -> Result<()> {
+    if image_id == CHECK_SCHEMA_BATCH_ID.into() {
+        print_batch_journal(&BatchJournal::abi_decode(journal)?);
+    } else if image_id == CHECK_SCHEMA_STREAM_ID.into() {
+        print_stream_journal(&StreamJournal::abi_decode(journal)?);
+    } else {
+        print_journal(&Journal::abi_decode(journal)?);
     }
-    #[test]
-    fn fail_case() {
-        let data = include_str!("../res/data_failcase.json");
-        let schema = include_str!("../res/schema.json");
+    Ok(())
+}
 
-        let outputs = check_schema(data, schema);
-        assert_eq!(outputs.result, 0, "The input data is satisfy the schema");
+fn print_journal(journal: &Journal) {
+    println!("journal version: {}", journal.version);
+    println!("data hash: {}", hex::encode(journal.data_hash));
+    println!("salted: {}", journal.salted);
+    println!("schema hash: {}", hex::encode(journal.schema_hash));
+    println!("draft: {}", journal.draft);
+    println!("valid: {}", journal.valid);
+    for error in &journal.errors {
+        println!("  {} at '{}'", error.keyword, error.instance_path);
+    }
+    for disclosure in &journal.disclosed {
+        match &disclosure.value {
+            Some(value) => println!("disclosed {}: {value}", disclosure.pointer),
+            None => println!("disclosed {}: (absent)", disclosure.pointer),
+        }
+    }
+    if let Some(outcome) = &journal.predicate {
+        println!("predicate '{}' holds: {}", outcome.predicate, outcome.holds);
     }
 }
+
+fn print_batch_journal(journal: &BatchJournal) {
+    println!("journal version: {}", journal.version);
+    println!("schema hash: {}", hex::encode(journal.schema_hash));
+    println!("draft: {}", journal.draft);
+    println!("documents: {}", journal.count);
+    println!("valid documents: {}", journal.valid_count());
+    println!("validity bitmap: {}", hex::encode(&journal.validity));
+    println!("merkle root: {}", hex::encode(journal.root));
+}
+
+fn print_stream_journal(journal: &StreamJournal) {
+    println!("journal version: {}", journal.version);
+    println!("schema hash: {}", hex::encode(journal.schema_hash));
+    println!("draft: {}", journal.draft);
+    println!("records: {}", journal.count);
+    println!("valid records: {}", journal.valid_count);
+    println!("all valid: {}", journal.all_valid());
+    println!("stream hash: {}", hex::encode(journal.stream_hash));
+}
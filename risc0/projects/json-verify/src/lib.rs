@@ -0,0 +1,205 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Verification of json-validate receipts for services that only verify.
+//!
+//! This crate depends on neither the prover, the guest methods nor a JSON Schema validator.
+//! Build it with `default-features = false` to leave out the command line tool as well.
+//!
+//! The check_schema image ID is a parameter rather than a constant: it is produced by building
+//! the guest, which a verifier does not do. Take it from the `image_id.dat` written by
+//! `json-validate prove`, or from `CHECK_SCHEMA_ID` in the methods crate.
+
+use std::fmt;
+
+use json_validate_core::{Journal, JournalError};
+use receipt_seal::{decode_seal, SealError};
+use risc0_zkp::verify::VerificationError;
+use risc0_zkvm::{sha::Digest, Receipt, ReceiptClaim, VerifierContext};
+
+/// A check_schema journal whose receipt verified against the expected image ID.
+///
+/// `journal.valid` may be false: a receipt can prove that a document does not conform.
+#[derive(Clone, Debug)]
+pub struct VerifiedDocument {
+    /// The image ID the receipt was verified against.
+    pub image_id: Digest,
+
+    /// The decoded journal.
+    pub journal: Journal,
+}
+
+/// Verify a check_schema receipt and check that it commits to `expected_schema_hash`.
+///
+/// The schema hash is the [schema_commitment](json_validate_core::schema_commitment) of the
+/// schema. It must be checked because the check_schema image accepts any schema as input.
+pub fn verify_schema_receipt(
+    receipt: &Receipt,
+    image_id: impl Into<Digest>,
+    expected_schema_hash: &[u8; 32],
+) -> Result<VerifiedDocument, VerifyError> {
+    let document = verify_receipt(receipt, &VerifierContext::default(), image_id)?;
+    if document.journal.schema_hash != *expected_schema_hash {
+        return Err(VerifyError::SchemaHash {
+            expected: *expected_schema_hash,
+            actual: document.journal.schema_hash,
+        });
+    }
+    Ok(document)
+}
+
+/// Verify `receipt` against `image_id` with `ctx` and decode its journal.
+///
+/// Unlike [verify_schema_receipt] this does not check which schema the journal commits to. Use
+/// it for the check_fixed_schema image, whose ID already determines the schema.
+pub fn verify_receipt(
+    receipt: &Receipt,
+    ctx: &VerifierContext,
+    image_id: impl Into<Digest>,
+) -> Result<VerifiedDocument, VerifyError> {
+    let image_id = image_id.into();
+    receipt
+        .verify_with_context(ctx, image_id)
+        .map_err(VerifyError::Receipt)?;
+    decode_document(image_id, &receipt.journal.bytes)
+}
+
+/// Verify a Groth16 seal and journal as written to `seal.dat` and `journal.dat`.
+///
+/// This mirrors the on-chain check: `seal` is the 4-byte selector followed by the Groth16 seal,
+/// and the claim is rebuilt from `image_id` and `journal`. The selector must match the digest of
+/// the default Groth16 verifier parameters. See [receipt_seal::decode_seal].
+pub fn verify_groth16_seal(
+    seal: &[u8],
+    journal: &[u8],
+    image_id: impl Into<Digest>,
+) -> Result<VerifiedDocument, VerifyError> {
+    let image_id = image_id.into();
+    let receipt = groth16_receipt(seal, journal, image_id)?;
+    verify_receipt(&receipt, &VerifierContext::default(), image_id)
+}
+
+/// The receipt for a Groth16 `seal` over `journal`, with the claim rebuilt from `image_id`.
+fn groth16_receipt(seal: &[u8], journal: &[u8], image_id: Digest) -> Result<Receipt, VerifyError> {
+    let claim = ReceiptClaim::ok(image_id, journal.to_vec());
+    let inner = decode_seal(seal, claim).map_err(VerifyError::Seal)?;
+    Ok(Receipt::new(inner, journal.to_vec()))
+}
+
+/// Decode the journal of a receipt that verified against `image_id`.
+fn decode_document(image_id: Digest, journal: &[u8]) -> Result<VerifiedDocument, VerifyError> {
+    let journal = Journal::abi_decode(journal).map_err(VerifyError::Journal)?;
+    Ok(VerifiedDocument { image_id, journal })
+}
+
+/// Errors returned when a receipt does not verify.
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The seal does not verify against the image ID.
+    Receipt(VerificationError),
+
+    /// The seal cannot be decoded, or its selector names other verifier parameters.
+    Seal(SealError),
+
+    /// The journal is not a json-validate journal of the current version.
+    Journal(JournalError),
+
+    /// The journal commits to a different schema.
+    SchemaHash {
+        expected: [u8; 32],
+        actual: [u8; 32],
+    },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Receipt(err) => write!(f, "receipt does not verify: {err}"),
+            VerifyError::Seal(err) => write!(f, "{err}"),
+            VerifyError::Journal(err) => write!(f, "{err}"),
+            VerifyError::SchemaHash { expected, actual } => write!(
+                f,
+                "schema hash mismatch: expected {}, journal has {}",
+                Digest::from(*expected),
+                Digest::from(*actual)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+#[cfg(test)]
+mod tests {
+    use json_validate_core::{SchemaDraft, JOURNAL_VERSION};
+
+    use super::*;
+
+    fn read_dat(name: &str) -> Vec<u8> {
+        let path = format!("{}/../json-validate/res/{name}", env!("CARGO_MANIFEST_DIR"));
+        hex::decode(std::fs::read_to_string(path).unwrap().trim()).unwrap()
+    }
+
+    #[test]
+    fn groth16_seal() {
+        let seal = read_dat("seal.dat");
+        let journal = read_dat("journal.dat");
+        let image_id: [u8; 32] = read_dat("image_id.dat").try_into().unwrap();
+
+        // The seal verifies. The fixture's journal predates the current layout, so the document
+        // itself does not decode; see `decode_current_journal` for that half.
+        let receipt = groth16_receipt(&seal, &journal, image_id.into()).unwrap();
+        receipt
+            .verify_with_context(&VerifierContext::default(), image_id)
+            .unwrap();
+        assert!(matches!(
+            verify_groth16_seal(&seal, &journal, image_id),
+            Err(VerifyError::Journal(_))
+        ));
+        assert!(matches!(
+            verify_groth16_seal(&seal, &journal, [0u8; 32]),
+            Err(VerifyError::Receipt(_))
+        ));
+
+        let mut other_selector = seal.clone();
+        other_selector[0] ^= 1;
+        assert!(matches!(
+            verify_groth16_seal(&other_selector, &journal, image_id),
+            Err(VerifyError::Seal(SealError::Selector { .. }))
+        ));
+        assert!(matches!(
+            verify_groth16_seal(&seal[..100], &journal, image_id),
+            Err(VerifyError::Seal(SealError::Groth16(_)))
+        ));
+    }
+
+    #[test]
+    fn decode_current_journal() {
+        let journal = Journal {
+            version: JOURNAL_VERSION,
+            data_hash: [1; 32],
+            salted: false,
+            schema_hash: [2; 32],
+            draft: SchemaDraft::Draft7,
+            valid: true,
+            errors: Vec::new(),
+            disclosed: Vec::new(),
+            predicate: None,
+        };
+        let image_id = Digest::from([3u32; 8]);
+        let document = decode_document(image_id, &journal.abi_encode()).unwrap();
+        assert_eq!(document.image_id, image_id);
+        assert_eq!(document.journal, journal);
+    }
+}
@@ -11,76 +11,258 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
-// use serde_json::json;
-// use jsonschema::{Draft, JSONSchema};
-// use json_validate_core::Outputs;
-// use json_validate_methods::{CHECK_SCHEMA_ELF,CHECK_SCHEMA_ID};
-use risc0_zkvm::{Receipt};
-use std::fs::File;
-use std::io::Read;
-pub struct Outputs {
-    pub result: u32,
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{error::ErrorKind, Args, CommandFactory, Parser};
+use json_validate_core::Journal;
+use json_verify::{verify_groth16_seal, verify_receipt, VerifyError};
+use receipt_store::ReceiptStore;
+use risc0_zkvm::{compute_image_id, sha::Digest, Receipt, VerifierContext};
+
+/// Verifies a json-validate receipt and checks what its journal commits to.
+///
+/// Exits with status 0 on success. On failure the reason is printed to stderr and the exit
+/// status identifies it: 1 unreadable input, 3 receipt does not verify, 4 journal cannot be
+/// decoded, 5 data hash mismatch, 6 schema hash mismatch, 7 document does not conform.
+#[derive(Parser)]
+#[command(about, version)]
+struct Cli {
+    /// Receipt file, as written by `json-validate prove`.
+    #[arg(required_unless_present_any = ["dat_dir", "id"])]
+    receipt: Option<PathBuf>,
+
+    /// Verify the seal.dat and journal.dat written by `json-validate prove --receipt-kind groth16`
+    /// in DIR instead of a receipt, as an on-chain verifier would. The image ID defaults to
+    /// DIR/image_id.dat.
+    #[arg(long, value_name = "DIR", conflicts_with = "receipt")]
+    dat_dir: Option<PathBuf>,
+
+    /// Verify the receipt stored as ID in the receipt store given by `--store` instead of a
+    /// receipt file. The image ID defaults to the one the receipt was stored under.
+    #[arg(long, requires = "store", conflicts_with_all = ["receipt", "dat_dir"])]
+    id: Option<String>,
+
+    /// Receipt store to look up `--id` in, as written by `json-validate prove --store`.
+    #[arg(long, value_name = "DIR", requires = "id")]
+    store: Option<PathBuf>,
+
+    #[command(flatten)]
+    image: ImageArgs,
+
+    /// Data hash the journal must commit to, hex-encoded.
+    #[arg(long, value_parser = parse_hash)]
+    data_hash: Option<[u8; 32]>,
+
+    /// Schema hash the journal must commit to, hex-encoded. Without it any schema is accepted
+    /// and a warning is printed.
+    #[arg(long, value_parser = parse_hash)]
+    schema_hash: Option<[u8; 32]>,
+
+    /// Accept receipts proving that the document does not conform to the schema.
+    #[arg(long)]
+    allow_invalid: bool,
+}
+
+#[derive(Args)]
+#[group(multiple = false)]
+struct ImageArgs {
+    /// Image ID the receipt must verify against, hex-encoded as in `image_id.dat`.
+    #[arg(long, value_parser = parse_hash)]
+    image_id: Option<[u8; 32]>,
+
+    /// Guest ELF to compute the image ID from.
+    #[arg(long)]
+    elf: Option<PathBuf>,
 }
-fn main() {
 
-    // Read the JSON string back from the file
-    let mut file = File::open("../json-validate/res/receipt_groth16.json").expect("failed to open");
-    let mut receipt_json = String::new();
-    file.read_to_string(&mut receipt_json).expect("failed to read");
-  
-    let new_hash_id: [u32; 8] = [3159902488, 1754129237, 2872742036, 2719751631, 866932760, 1147298780, 535036495, 1127565503];
+/// Why verification failed. Each reason exits with its own status code.
+#[derive(Debug)]
+enum Failure {
+    /// The receipt or ELF could not be read.
+    Input(String),
 
-    let receipt = serde_json::from_str::<Receipt>(&receipt_json).unwrap();
-    let flag = receipt.verify(new_hash_id).unwrap();
+    /// The receipt does not verify, or commits to a different schema.
+    Verify(VerifyError),
 
-    let output:u32 = receipt.journal.decode().unwrap();
+    /// The journal commits to different data.
+    DataHash {
+        expected: [u8; 32],
+        actual: [u8; 32],
+    },
 
-    println!("Output {}", output);
-    println!("Flag {:?}", flag)
+    /// The receipt proves that the document does not conform.
+    Invalid(Box<Journal>),
 }
 
-/* 
-fn benchmark_prove(data: &str, schema: &str) {
-    // start benchmarks
-    const ITER: usize = 3;
-    let mut benches = Vec::new();
-    let mut benches_verify = Vec::new();
-
-    let input = (data, schema);
-    for _ in 0..ITER {
-        // Obtain the default prover.
-        let prover = default_prover();
-
-        let env = ExecutorEnv::builder()
-            .write(&input)
-            .unwrap()
-            .build()
-            .unwrap();
-
-        let before = Instant::now();
-        // Produce a receipt by proving the specified ELF binary.
-        let receipt  = prover.prove(env, CHECK_SCHEMA_ELF).unwrap().receipt;
-        // println!("\n###### Time: {:.2?}", before.elapsed());
-        benches.push(before.elapsed());
-
-        let before_verify = Instant::now();
-        receipt.verify(CHECK_SCHEMA_ID).unwrap();
-        benches_verify.push(before_verify.elapsed());
+impl Failure {
+    fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Failure::Input(_) => 1,
+            Failure::Verify(VerifyError::Receipt(_) | VerifyError::Seal(_)) => 3,
+            Failure::Verify(VerifyError::Journal(_)) => 4,
+            Failure::DataHash { .. } => 5,
+            Failure::Verify(VerifyError::SchemaHash { .. }) => 6,
+            Failure::Invalid(_) => 7,
+        })
     }
+}
 
-    println!("\n-------- BENCHMARK VERIFY ---------");
-    for bench in benches_verify {
-        println!("{:.2?}", bench);
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Input(err) => write!(f, "{err}"),
+            Failure::Verify(err) => write!(f, "{err}"),
+            Failure::DataHash { expected, actual } => write!(
+                f,
+                "data hash mismatch: expected {}, journal has {}",
+                hex::encode(expected),
+                hex::encode(actual)
+            ),
+            Failure::Invalid(journal) => {
+                write!(f, "document does not conform to the schema")?;
+                for error in &journal.errors {
+                    write!(f, "\n  {} at '{}'", error.keyword, error.instance_path)?;
+                }
+                Ok(())
+            }
+        }
     }
-    println!("\n---------------------------");
-    
+}
 
-    println!("\n-------- BENCHMARK ---------");
-    for bench in benches {
-        println!("{:.2?}", bench);
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if cli.dat_dir.is_none()
+        && cli.id.is_none()
+        && cli.image.image_id.is_none()
+        && cli.image.elf.is_none()
+    {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "one of --image-id or --elf is required unless --dat-dir or --id is given",
+            )
+            .exit();
     }
-    println!("\n---------------------------");
-    // end benchmarks
 
+    match verify(&cli) {
+        Ok(journal) => {
+            println!("receipt OK");
+            println!("data hash: {}", hex::encode(journal.data_hash));
+            println!("schema hash: {}", hex::encode(journal.schema_hash));
+            println!("draft: {}", journal.draft);
+            println!("valid: {}", journal.valid);
+            // A check_schema receipt verifies for any schema, so without --schema-hash the OK
+            // above says nothing about which schema the document conforms to.
+            if cli.schema_hash.is_none() {
+                eprintln!(
+                    "warning: the schema was not checked; pass --schema-hash unless the image ID \
+                     is a check_fixed_schema image that embeds the schema"
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(failure) => {
+            eprintln!("verification failed: {failure}");
+            failure.exit_code()
+        }
+    }
+}
+
+fn verify(cli: &Cli) -> Result<Journal, Failure> {
+    let stored = match (&cli.store, &cli.id) {
+        (Some(dir), Some(id)) => {
+            let store = ReceiptStore::open(dir)
+                .map_err(|err| Failure::Input(format!("{}: {err}", dir.display())))?;
+            let entry = store
+                .entry(id)
+                .map_err(|err| Failure::Input(err.to_string()))?;
+            let receipt = store
+                .get(id)
+                .map_err(|err| Failure::Input(err.to_string()))?;
+            Some((entry, receipt))
+        }
+        _ => None,
+    };
+
+    let image_id = match (&cli.image.image_id, &cli.image.elf, &cli.dat_dir, &stored) {
+        (Some(image_id), _, _, _) => Digest::from(*image_id),
+        (None, Some(elf), _, _) => {
+            let elf = fs::read(elf)
+                .map_err(|err| Failure::Input(format!("reading {}: {err}", elf.display())))?;
+            compute_image_id(&elf).map_err(|err| Failure::Input(format!("invalid ELF: {err}")))?
+        }
+        (None, None, Some(dir), _) => {
+            let path = dir.join("image_id.dat");
+            let image_id = read_dat(&path)?;
+            Digest::try_from(image_id.as_slice()).map_err(|_| {
+                Failure::Input(format!("{}: expected a 32-byte image ID", path.display()))
+            })?
+        }
+        (None, None, None, Some((entry, _))) => Digest::from(entry.index.image_id),
+        (None, None, None, None) => unreachable!("main requires an image ID source"),
+    };
+
+    let journal = match (&cli.receipt, &cli.dat_dir, &stored) {
+        (Some(path), _, _) => {
+            let receipt_json = fs::read_to_string(path)
+                .map_err(|err| Failure::Input(format!("reading {}: {err}", path.display())))?;
+            let receipt: Receipt = serde_json::from_str(&receipt_json)
+                .map_err(|err| Failure::Input(format!("parsing {}: {err}", path.display())))?;
+            verify_receipt(&receipt, &VerifierContext::default(), image_id)
+        }
+        (None, Some(dir), _) => {
+            let seal = read_dat(&dir.join("seal.dat"))?;
+            let journal = read_dat(&dir.join("journal.dat"))?;
+            verify_groth16_seal(&seal, &journal, image_id)
+        }
+        (None, None, Some((_, receipt))) => {
+            verify_receipt(receipt, &VerifierContext::default(), image_id)
+        }
+        (None, None, None) => unreachable!("clap requires a receipt, --dat-dir or --id"),
+    }
+    .map_err(Failure::Verify)?
+    .journal;
+
+    if let Some(expected) = cli.schema_hash {
+        if expected != journal.schema_hash {
+            return Err(Failure::Verify(VerifyError::SchemaHash {
+                expected,
+                actual: journal.schema_hash,
+            }));
+        }
+    }
+
+    if let Some(expected) = cli.data_hash {
+        if expected != journal.data_hash {
+            return Err(Failure::DataHash {
+                expected,
+                actual: journal.data_hash,
+            });
+        }
+    }
+    if !journal.valid && !cli.allow_invalid {
+        return Err(Failure::Invalid(Box::new(journal)));
+    }
+
+    Ok(journal)
+}
+
+/// Read a hex-encoded `.dat` file as written by `json-validate prove`.
+fn read_dat(path: &Path) -> Result<Vec<u8>, Failure> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| Failure::Input(format!("reading {}: {err}", path.display())))?;
+    hex::decode(contents.trim())
+        .map_err(|err| Failure::Input(format!("parsing {}: {err}", path.display())))
+}
+
+fn parse_hash(hex_hash: &str) -> Result<[u8; 32], String> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(hex_hash.trim_start_matches("0x"), &mut hash)
+        .map_err(|err| format!("expected 32 hex-encoded bytes: {err}"))?;
+    Ok(hash)
 }
-*/
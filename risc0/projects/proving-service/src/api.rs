@@ -0,0 +1,260 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use receipt_store::StoreError;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{EncryptRequest, JobStatus, Service, ValidateRequest};
+
+/// Response to a queued job: poll `GET /jobs/{id}` for its status.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Submitted {
+    pub id: Uuid,
+}
+
+/// The routes of the service, see the [crate] docs.
+pub fn router(service: Service) -> Router {
+    Router::new()
+        .route("/validate", post(validate))
+        .route("/encrypt", post(encrypt))
+        .route("/jobs/:id", get(job))
+        .route("/receipts/:id", get(receipt))
+        .with_state(service)
+}
+
+async fn validate(
+    State(service): State<Service>,
+    Json(request): Json<ValidateRequest>,
+) -> Result<(StatusCode, Json<Submitted>), ApiError> {
+    let id = service.submit_validate(request)?;
+    Ok((StatusCode::ACCEPTED, Json(Submitted { id })))
+}
+
+async fn encrypt(
+    State(service): State<Service>,
+    Json(request): Json<EncryptRequest>,
+) -> Result<(StatusCode, Json<Submitted>), ApiError> {
+    let id = service.submit_encrypt(request)?;
+    Ok((StatusCode::ACCEPTED, Json(Submitted { id })))
+}
+
+async fn job(
+    State(service): State<Service>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobStatus>, ApiError> {
+    service.status(id)?.map(Json).ok_or(ApiError::NotFound(id))
+}
+
+async fn receipt(
+    State(service): State<Service>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, ApiError> {
+    if let Some(receipt) = service.store().receipt(id)? {
+        return Ok(Json(receipt).into_response());
+    }
+    match service.status(id)? {
+        Some(JobStatus::Queued | JobStatus::Running) => Err(ApiError::NotReady(id)),
+        _ => Err(ApiError::NotFound(id)),
+    }
+}
+
+/// Errors returned by the routes, as a status code and a JSON `{"error": ...}` body.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request is malformed. Responds with 400.
+    BadRequest(String),
+
+    /// There is no such job, or it failed without a receipt. Responds with 404.
+    NotFound(Uuid),
+
+    /// The job has not finished. Responds with 409.
+    NotReady(Uuid),
+
+    /// Reading the receipt store failed. Responds with 500.
+    Store(StoreError),
+}
+
+impl ApiError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::NotReady(_) => StatusCode::CONFLICT,
+            ApiError::Store(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<StoreError> for ApiError {
+    fn from(err: StoreError) -> Self {
+        ApiError::Store(err)
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::BadRequest(err) => write!(f, "{err}"),
+            ApiError::NotFound(id) => write!(f, "no receipt or job {id}"),
+            ApiError::NotReady(id) => write!(f, "job {id} has not finished"),
+            ApiError::Store(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.to_string() });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Request};
+    use encrypt_aesgcm_core::{decrypt, Algorithm, NONCE_LEN};
+    use json_validate::ReceiptKind;
+    use risc0_zkvm::Receipt;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{JobResult, JobStore};
+
+    fn service() -> (Router, TempDir) {
+        // Fake receipts, so a job finishes as soon as the guest has executed.
+        std::env::set_var("RISC0_DEV_MODE", "1");
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::open(dir.path()).unwrap();
+        (router(Service::start(store, ReceiptKind::Groth16)), dir)
+    }
+
+    async fn call(router: &Router, method: &str, uri: &str, body: serde_json::Value) -> Response {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        router.clone().oneshot(request).await.unwrap()
+    }
+
+    async fn json<T: serde::de::DeserializeOwned>(response: Response) -> T {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    async fn finish(router: &Router, response: Response) -> (Uuid, JobResult) {
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let Submitted { id } = json(response).await;
+        loop {
+            let response = call(router, "GET", &format!("/jobs/{id}"), Default::default()).await;
+            match json(response).await {
+                JobStatus::Queued | JobStatus::Running => {
+                    tokio::time::sleep(Duration::from_millis(100)).await
+                }
+                JobStatus::Done(result) => return (id, result),
+                JobStatus::Failed { error } => panic!("job failed: {error}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn validate() {
+        let (router, dir) = service();
+        let data = include_str!("../../json-validate/res/data.json");
+        let schema = include_str!("../../json-validate/res/schema.json");
+        let body = serde_json::json!({
+            "data": serde_json::from_str::<serde_json::Value>(data).unwrap(),
+            "schema": serde_json::from_str::<serde_json::Value>(schema).unwrap(),
+        });
+        let response = call(&router, "POST", "/validate", body).await;
+        let (id, result) = finish(&router, response).await;
+        assert_eq!(result.valid, Some(true));
+
+        let response = call(
+            &router,
+            "GET",
+            &format!("/receipts/{id}"),
+            Default::default(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let receipt: Receipt = json(response).await;
+        assert_eq!(hex::encode(&receipt.journal.bytes), result.journal);
+
+        // The receipt is in the shared receipt store, indexed by its journal.
+        let store = receipt_store::ReceiptStore::open(dir.path()).unwrap();
+        let entry = store.entry(&result.receipt_id).unwrap();
+        assert_eq!(entry.index.label, "check_schema");
+        assert!(entry.index.schema_hash.is_some());
+    }
+
+    #[tokio::test]
+    async fn encrypt() {
+        let (router, _dir) = service();
+        let body = serde_json::json!({
+            "plaintext": "{\"name\": \"Alice\"}",
+            "key": hex::encode([7u8; 32]),
+            "aad": "record 42",
+        });
+        let response = call(&router, "POST", "/encrypt", body).await;
+        let (_, result) = finish(&router, response).await;
+
+        let stored = hex::decode(result.ciphertext.unwrap()).unwrap();
+        let (nonce, ciphertext) = stored.split_at(NONCE_LEN);
+        let plaintext = decrypt(
+            Algorithm::Aes256Gcm,
+            &[7u8; 32],
+            nonce,
+            b"record 42",
+            ciphertext,
+        )
+        .unwrap();
+        assert_eq!(plaintext, b"{\"name\": \"Alice\"}");
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let (router, _dir) = service();
+        let body = serde_json::json!({ "data": {}, "salt": "00" });
+        let response = call(&router, "POST", "/validate", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = serde_json::json!({ "plaintext": "{}", "key": hex::encode([7u8; 16]) });
+        let response = call(&router, "POST", "/encrypt", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let id = Uuid::new_v4();
+        for uri in [format!("/jobs/{id}"), format!("/receipts/{id}")] {
+            let response = call(&router, "GET", &uri, Default::default()).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
+}
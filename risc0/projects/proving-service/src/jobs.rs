@@ -0,0 +1,359 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{ensure, Context, Result};
+use encrypt_aesgcm_core::{
+    ciphertext_hash, encrypt, parse_key, stored_ciphertext, Algorithm, EncryptInput,
+    EncryptJournal, KeySource, KEY_SALT_LEN, NONCE_LEN,
+};
+use encrypt_aesgcm_methods::{ENCRYPT_AESGCM_ELF, ENCRYPT_AESGCM_ID};
+use json_validate::{prove, prove_schema, select_draft, ReceiptKind, SchemaOptions};
+use json_validate_core::{Predicate, SALT_LEN};
+use rand::{rngs::OsRng, RngCore};
+use receipt_store::Index;
+use risc0_zkvm::{ExecutorEnv, Receipt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::{ApiError, JobStore};
+
+/// Body of `POST /validate`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ValidateRequest {
+    /// The JSON document.
+    pub data: serde_json::Value,
+
+    /// The JSON schema. Without one the document is checked against the fixed schema.
+    #[serde(default)]
+    pub schema: Option<serde_json::Value>,
+
+    /// JSON Schema draft, as for `json-validate --draft`.
+    #[serde(default)]
+    pub draft: Option<String>,
+
+    /// JSON pointers to fields whose values are disclosed in the journal.
+    #[serde(default)]
+    pub disclose: Vec<String>,
+
+    /// Predicate evaluated over the data, e.g. `/age >= 18`.
+    #[serde(default)]
+    pub predicate: Option<String>,
+
+    /// 32-byte hex salt that blinds the committed data hash.
+    #[serde(default)]
+    pub salt: Option<String>,
+}
+
+/// Body of `POST /encrypt`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EncryptRequest {
+    /// The document to encrypt.
+    pub plaintext: String,
+
+    /// The key, as hex digits or base64.
+    pub key: String,
+
+    /// `aes-256-gcm` (the default), `aes-128-gcm` or `chacha20-poly1305`.
+    #[serde(default)]
+    pub algorithm: Option<String>,
+
+    /// Additional authenticated data.
+    #[serde(default)]
+    pub aad: String,
+
+    /// 32-byte hex salt for the key commitment. A random one is returned in the result if
+    /// omitted.
+    #[serde(default)]
+    pub key_salt: Option<String>,
+
+    /// Commit the ciphertext itself to the journal, not just its hash.
+    #[serde(default)]
+    pub commit_ciphertext: bool,
+}
+
+/// Where a job is, as returned by `GET /jobs/{id}`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Waiting for the prover.
+    Queued,
+
+    /// Being proven.
+    Running,
+
+    /// Proven. The receipt is available from `GET /receipts/{id}`.
+    Done(JobResult),
+
+    /// Proving failed. Failures are not stored, so they are forgotten on restart.
+    Failed { error: String },
+}
+
+/// What a finished job proved. Byte strings are hex-encoded.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct JobResult {
+    /// The image ID the receipt verifies against.
+    pub image_id: String,
+
+    /// The journal, as in `journal.dat`.
+    pub journal: String,
+
+    /// ID of the receipt in the receipt store, for `json-validate list` and `json-verify --id`.
+    pub receipt_id: String,
+
+    /// Whether the document conforms to the schema, for validation jobs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid: Option<bool>,
+
+    /// The nonce followed by the ciphertext, as stored by `encrypt-aesgcm`, for encryption jobs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ciphertext: Option<String>,
+
+    /// The salt of the key commitment, for encryption jobs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_salt: Option<String>,
+}
+
+/// A request checked and ready for the prover.
+enum Task {
+    Validate {
+        data: String,
+        schema: Option<String>,
+        opts: SchemaOptions,
+    },
+    Encrypt(EncryptInput),
+}
+
+/// The job queue and the statuses of the unfinished and failed jobs submitted since the service
+/// started. Finished jobs are only kept in the store.
+///
+/// Cloning is cheap: clones share the queue.
+#[derive(Clone)]
+pub struct Service {
+    jobs: Arc<Mutex<HashMap<Uuid, JobStatus>>>,
+    queue: mpsc::UnboundedSender<(Uuid, Task)>,
+    store: JobStore,
+    receipt_kind: ReceiptKind,
+}
+
+impl Service {
+    /// Start the worker that proves queued jobs and stores their receipts in `store`.
+    ///
+    /// Must be called from within a Tokio runtime. Jobs are proven one at a time, since a single
+    /// proof already uses every core.
+    pub fn start(store: JobStore, receipt_kind: ReceiptKind) -> Self {
+        let (queue, receiver) = mpsc::unbounded_channel();
+        let service = Service {
+            jobs: Arc::default(),
+            queue,
+            store,
+            receipt_kind,
+        };
+        tokio::spawn(service.clone().run(receiver));
+        service
+    }
+
+    /// The store of finished jobs.
+    pub fn store(&self) -> &JobStore {
+        &self.store
+    }
+
+    /// Queue a validation proof.
+    pub fn submit_validate(&self, request: ValidateRequest) -> Result<Uuid, ApiError> {
+        let schema = request.schema.as_ref().map(serde_json::Value::to_string);
+        let draft = request
+            .draft
+            .map(|draft| draft.parse())
+            .transpose()
+            .map_err(|err| ApiError::BadRequest(format!("draft: {err}")))?;
+        if let Some(schema) = &schema {
+            select_draft(schema, draft).map_err(|err| ApiError::BadRequest(format!("{err:#}")))?;
+        }
+        let predicate = request
+            .predicate
+            .map(|predicate| predicate.parse::<Predicate>())
+            .transpose()
+            .map_err(|err| ApiError::BadRequest(format!("predicate: {err}")))?;
+        let salt = request
+            .salt
+            .map(|salt| parse_hex::<SALT_LEN>("salt", &salt))
+            .transpose()?;
+
+        let opts = SchemaOptions {
+            draft,
+            disclose: request.disclose,
+            predicate,
+            salt,
+            receipt_kind: self.receipt_kind,
+        };
+        Ok(self.submit(Task::Validate {
+            data: request.data.to_string(),
+            schema,
+            opts,
+        }))
+    }
+
+    /// Queue an encryption proof under a fresh random nonce.
+    pub fn submit_encrypt(&self, request: EncryptRequest) -> Result<Uuid, ApiError> {
+        let algorithm: Algorithm = match request.algorithm {
+            Some(algorithm) => algorithm
+                .parse()
+                .map_err(|err| ApiError::BadRequest(format!("{err}")))?,
+            None => Algorithm::default(),
+        };
+        let key = parse_key(&request.key).map_err(|err| ApiError::BadRequest(format!("{err}")))?;
+        if key.len() != algorithm.key_len() {
+            return Err(ApiError::BadRequest(format!(
+                "key is {} bytes, {algorithm} needs {}",
+                key.len(),
+                algorithm.key_len()
+            )));
+        }
+        let key_salt = match request.key_salt {
+            Some(salt) => parse_hex::<KEY_SALT_LEN>("key_salt", &salt)?,
+            None => random_bytes(),
+        };
+
+        Ok(self.submit(Task::Encrypt(EncryptInput {
+            algorithm,
+            key: KeySource::Raw(key),
+            key_salt,
+            aad: request.aad.into_bytes(),
+            plaintext: request.plaintext,
+            nonce: random_bytes::<NONCE_LEN>().to_vec(),
+            commit_ciphertext: request.commit_ciphertext,
+        })))
+    }
+
+    /// The status of job `id`. Finished jobs are found in the store.
+    pub fn status(&self, id: Uuid) -> Result<Option<JobStatus>, ApiError> {
+        if let Some(status) = self.jobs.lock().unwrap().get(&id) {
+            return Ok(Some(status.clone()));
+        }
+        Ok(self.store.result(id)?.map(JobStatus::Done))
+    }
+
+    fn submit(&self, task: Task) -> Uuid {
+        let id = Uuid::new_v4();
+        self.set_status(id, JobStatus::Queued);
+        // The worker holds a clone of the service, so the receiver is never dropped first.
+        let _ = self.queue.send((id, task));
+        id
+    }
+
+    fn set_status(&self, id: Uuid, status: JobStatus) {
+        self.jobs.lock().unwrap().insert(id, status);
+    }
+
+    async fn run(self, mut receiver: mpsc::UnboundedReceiver<(Uuid, Task)>) {
+        while let Some((id, task)) = receiver.recv().await {
+            self.set_status(id, JobStatus::Running);
+            let (store, receipt_kind) = (self.store.clone(), self.receipt_kind);
+            let proven = tokio::task::spawn_blocking(move || {
+                let (receipt, index, result) = task.prove(receipt_kind)?;
+                store
+                    .put(id, &receipt, index, result)
+                    .context("storing the receipt")
+            })
+            .await;
+            let error = match proven {
+                Ok(Ok(_)) => None,
+                Ok(Err(err)) => Some(format!("{err:#}")),
+                Err(err) => Some(format!("prover panicked: {err}")),
+            };
+            match error {
+                // The store answers for the job from here on, so the map does not grow with
+                // every job served.
+                None => {
+                    self.jobs.lock().unwrap().remove(&id);
+                }
+                Some(error) => self.set_status(id, JobStatus::Failed { error }),
+            }
+        }
+    }
+}
+
+impl Task {
+    /// Prove the task, returning the receipt, its index in the receipt store and the result.
+    fn prove(self, receipt_kind: ReceiptKind) -> Result<(Receipt, Index, JobResult)> {
+        match self {
+            Task::Validate { data, schema, opts } => {
+                let proof = prove_schema(&data, schema.as_deref(), &opts)?;
+                let index = proof.index();
+                let result = JobResult {
+                    image_id: hex::encode(proof.image_id),
+                    journal: hex::encode(&proof.receipt.journal.bytes),
+                    receipt_id: String::new(),
+                    valid: Some(proof.journal.valid),
+                    ciphertext: None,
+                    key_salt: None,
+                };
+                Ok((proof.receipt, index, result))
+            }
+            Task::Encrypt(input) => {
+                let env = ExecutorEnv::builder().write(&input)?.build()?;
+                let receipt = prove(env, ENCRYPT_AESGCM_ELF, ENCRYPT_AESGCM_ID, receipt_kind)?;
+                let journal = EncryptJournal::abi_decode(&receipt.journal.bytes)
+                    .context("decoding journal data")?;
+
+                // As in `encrypt-aesgcm`, reproduce the ciphertext unless it was committed.
+                let ciphertext = match journal.ciphertext {
+                    Some(ciphertext) => ciphertext,
+                    None => {
+                        let key = input.key.derive(input.algorithm);
+                        let plaintext = input.plaintext.as_bytes();
+                        encrypt(input.algorithm, &key, &input.nonce, &input.aad, plaintext)?
+                    }
+                };
+                ensure!(
+                    ciphertext_hash(&ciphertext) == journal.ciphertext_hash,
+                    "the journal commits to a different ciphertext"
+                );
+
+                let result = JobResult {
+                    image_id: hex::encode(risc0_zkvm::sha::Digest::from(ENCRYPT_AESGCM_ID)),
+                    journal: hex::encode(&receipt.journal.bytes),
+                    receipt_id: String::new(),
+                    valid: None,
+                    ciphertext: Some(hex::encode(stored_ciphertext(&input.nonce, &ciphertext))),
+                    key_salt: Some(hex::encode(input.key_salt)),
+                };
+                let index = Index {
+                    data_hash: Some(journal.plaintext_hash),
+                    ..Index::new(ENCRYPT_AESGCM_ID, "encrypt_aesgcm")
+                };
+                Ok((receipt, index, result))
+            }
+        }
+    }
+}
+
+fn parse_hex<const N: usize>(name: &str, hex_bytes: &str) -> Result<[u8; N], ApiError> {
+    let mut bytes = [0u8; N];
+    hex::decode_to_slice(hex_bytes, &mut bytes).map_err(|err| {
+        ApiError::BadRequest(format!("{name} must be {N} hex-encoded bytes: {err}"))
+    })?;
+    Ok(bytes)
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
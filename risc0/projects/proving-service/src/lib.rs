@@ -0,0 +1,32 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An HTTP service that proves the json-validate and encrypt-aesgcm guests.
+//!
+//! Requests are queued and proven one at a time with the default prover, so a long proof does
+//! not block the server. Receipts are written to a [receipt_store::ReceiptStore] directory,
+//! where they outlive the process and can be listed with `json-validate list`. The routes are:
+//!
+//! - `POST /validate` queues a schema validation proof, see [ValidateRequest].
+//! - `POST /encrypt` queues an AES-GCM encryption proof, see [EncryptRequest].
+//! - `GET /jobs/{id}` reports the [JobStatus] of a job.
+//! - `GET /receipts/{id}` returns the receipt of a finished job.
+
+mod api;
+mod jobs;
+mod store;
+
+pub use api::{router, ApiError, Submitted};
+pub use jobs::{EncryptRequest, JobResult, JobStatus, Service, ValidateRequest};
+pub use store::JobStore;
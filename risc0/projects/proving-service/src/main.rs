@@ -0,0 +1,54 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{net::SocketAddr, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use json_validate::ReceiptKind;
+use proving_service::{router, JobStore, Service};
+use tokio::net::TcpListener;
+
+/// Serves json-validate and encrypt-aesgcm proofs over HTTP.
+#[derive(Parser)]
+#[command(about, version)]
+struct Cli {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:3000")]
+    listen: SocketAddr,
+
+    /// Receipt store the receipts are added to, as for `json-validate prove --store`.
+    #[arg(long, default_value = "./receipts")]
+    store: PathBuf,
+
+    /// The kind of receipt to produce.
+    #[arg(long, default_value_t = ReceiptKind::Groth16)]
+    receipt_kind: ReceiptKind,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let store = JobStore::open(&cli.store)
+        .with_context(|| format!("opening receipt store {}", cli.store.display()))?;
+    let service = Service::start(store, cli.receipt_kind);
+
+    let listener = TcpListener::bind(cli.listen)
+        .await
+        .with_context(|| format!("binding {}", cli.listen))?;
+    println!("listening on {}", listener.local_addr()?);
+    axum::serve(listener, router(service)).await?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use receipt_store::{Index, ReceiptStore, StoreError};
+use risc0_zkvm::Receipt;
+use uuid::Uuid;
+
+use crate::JobResult;
+
+/// The results of finished jobs, with their receipts in a [ReceiptStore].
+///
+/// Receipts are stored like those of `json-validate --store`, so `json-validate list` and
+/// `json-verify --store` work on the same directory. Each job's [JobResult] is kept in
+/// `jobs/<id>.json` and names its receipt by [JobResult::receipt_id].
+#[derive(Clone, Debug)]
+pub struct JobStore {
+    receipts: ReceiptStore,
+    jobs: PathBuf,
+}
+
+impl JobStore {
+    /// Open the store in `dir`, creating the directories if needed.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let receipts = ReceiptStore::open(&dir)?;
+        let jobs = dir.as_ref().join("jobs");
+        fs::create_dir_all(&jobs)?;
+        Ok(JobStore { receipts, jobs })
+    }
+
+    /// The receipt store the receipts are kept in.
+    pub fn receipts(&self) -> &ReceiptStore {
+        &self.receipts
+    }
+
+    /// Store the receipt of job `id` under `index`, and its result with the receipt's ID
+    /// filled in.
+    ///
+    /// The result is written last, so a job with a result always has a receipt.
+    pub fn put(
+        &self,
+        id: Uuid,
+        receipt: &Receipt,
+        index: Index,
+        mut result: JobResult,
+    ) -> Result<JobResult, StoreError> {
+        result.receipt_id = self.receipts.put(receipt, index)?.id;
+        fs::write(self.job_path(id), serde_json::to_vec_pretty(&result)?)?;
+        Ok(result)
+    }
+
+    /// The receipt of job `id`, if the job has finished.
+    pub fn receipt(&self, id: Uuid) -> Result<Option<Receipt>, StoreError> {
+        self.result(id)?
+            .map(|result| self.receipts.get(&result.receipt_id))
+            .transpose()
+    }
+
+    /// The result of job `id`, if the job has finished.
+    pub fn result(&self, id: Uuid) -> Result<Option<JobResult>, StoreError> {
+        match fs::read(self.job_path(id)) {
+            Ok(result_json) => Ok(Some(serde_json::from_slice(&result_json)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn job_path(&self, id: Uuid) -> PathBuf {
+        self.jobs.join(format!("{id}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use receipt_store::Query;
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, ReceiptClaim};
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = JobStore::open(dir.path().join("receipts")).unwrap();
+        let id = Uuid::new_v4();
+        assert!(store.receipt(id).unwrap().is_none());
+        assert!(store.result(id).unwrap().is_none());
+
+        let claim = ReceiptClaim::ok([1u32; 8], vec![2, 3]);
+        let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), vec![2, 3]);
+        let result = JobResult {
+            image_id: hex::encode([1u8; 32]),
+            journal: "0203".into(),
+            receipt_id: String::new(),
+            valid: Some(true),
+            ciphertext: None,
+            key_salt: None,
+        };
+        let index = Index::new([1u32; 8], "test");
+        let result = store.put(id, &receipt, index, result).unwrap();
+
+        let stored = store.receipt(id).unwrap().unwrap();
+        assert_eq!(stored.journal.bytes, receipt.journal.bytes);
+        assert_eq!(store.result(id).unwrap(), Some(result.clone()));
+
+        // The receipt is listed by the shared store under the ID the result names.
+        let entries = store.receipts().list(&Query::default()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, result.receipt_id);
+    }
+}
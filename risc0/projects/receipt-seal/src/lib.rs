@@ -0,0 +1,230 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encoding of receipts as the selector-prefixed seals accepted by the RISC Zero verifier
+//! contracts.
+//!
+//! A seal starts with a 4-byte selector naming the verifier that accepts it. Groth16 seals use
+//! the first 4 bytes of the verifier parameters digest, as the `RiscZeroVerifierRouter` does.
+//! Fake receipts are encoded for the `RiscZeroMockVerifier` as [MOCK_SELECTOR] followed by the
+//! claim digest.
+//!
+//! Succinct and composite receipts have no on-chain verifier. Compress them to Groth16 with
+//! `ProverOpts::groth16()` before encoding.
+
+use std::fmt;
+
+use risc0_zkvm::{
+    sha::{Digest, Digestible},
+    FakeReceipt, Groth16Receipt, Groth16ReceiptVerifierParameters, Groth16Seal, InnerReceipt,
+    MaybePruned, ReceiptClaim,
+};
+
+/// Length of the selector that prefixes every seal.
+pub const SELECTOR_LEN: usize = 4;
+
+/// Selector of seals for the mock verifier, which encode a [FakeReceipt].
+pub const MOCK_SELECTOR: [u8; SELECTOR_LEN] = [0; SELECTOR_LEN];
+
+/// The selector of Groth16 seals verified with `verifier_parameters`.
+pub fn groth16_selector(verifier_parameters: &Digest) -> [u8; SELECTOR_LEN] {
+    verifier_parameters.as_bytes()[..SELECTOR_LEN]
+        .try_into()
+        .unwrap()
+}
+
+/// Encode `inner` as a selector-prefixed seal.
+pub fn encode_seal(inner: &InnerReceipt) -> Result<Vec<u8>, SealError> {
+    let (selector, seal) = match inner {
+        InnerReceipt::Fake(receipt) => (MOCK_SELECTOR, receipt.claim.digest().as_bytes().to_vec()),
+        InnerReceipt::Groth16(receipt) => (
+            groth16_selector(&receipt.verifier_parameters),
+            receipt.seal.clone(),
+        ),
+        InnerReceipt::Succinct(_) => return Err(SealError::Unsupported("succinct")),
+        InnerReceipt::Composite(_) => return Err(SealError::Unsupported("composite")),
+        _ => return Err(SealError::Unsupported("unknown")),
+    };
+    let mut selector_seal = Vec::with_capacity(SELECTOR_LEN + seal.len());
+    selector_seal.extend_from_slice(&selector);
+    selector_seal.extend_from_slice(&seal);
+    Ok(selector_seal)
+}
+
+/// Decode a seal produced by [encode_seal] for a receipt of `claim`.
+///
+/// Groth16 seals must carry the selector of the default [Groth16ReceiptVerifierParameters].
+pub fn decode_seal(
+    seal: &[u8],
+    claim: impl Into<MaybePruned<ReceiptClaim>>,
+) -> Result<InnerReceipt, SealError> {
+    decode_seal_with_parameters(
+        seal,
+        claim,
+        Groth16ReceiptVerifierParameters::default().digest(),
+    )
+}
+
+/// Decode a seal produced by [encode_seal], for Groth16 seals verified with
+/// `verifier_parameters`.
+///
+/// The seal does not contain the claim, so it is rebuilt by the caller, usually with
+/// [ReceiptClaim::ok] from the image ID and journal. The result still has to be verified.
+pub fn decode_seal_with_parameters(
+    seal: &[u8],
+    claim: impl Into<MaybePruned<ReceiptClaim>>,
+    verifier_parameters: Digest,
+) -> Result<InnerReceipt, SealError> {
+    let claim = claim.into();
+    let Some((selector, seal)) = seal.split_at_checked(SELECTOR_LEN) else {
+        return Err(SealError::Truncated(seal.len()));
+    };
+
+    if selector == MOCK_SELECTOR {
+        let digest = Digest::try_from(seal).map_err(|_| SealError::Truncated(seal.len()))?;
+        if digest != claim.digest() {
+            return Err(SealError::ClaimDigest);
+        }
+        return Ok(InnerReceipt::Fake(FakeReceipt::new(claim)));
+    }
+
+    let expected = groth16_selector(&verifier_parameters);
+    if selector != expected {
+        return Err(SealError::Selector {
+            expected,
+            actual: selector.try_into().unwrap(),
+        });
+    }
+    let seal = Groth16Seal::from_vec(seal).map_err(|err| SealError::Groth16(err.to_string()))?;
+    Ok(InnerReceipt::Groth16(Groth16Receipt::new(
+        seal.to_vec(),
+        claim,
+        verifier_parameters,
+    )))
+}
+
+/// Errors encoding or decoding a seal.
+#[derive(Debug)]
+pub enum SealError {
+    /// No verifier contract accepts this kind of receipt.
+    Unsupported(&'static str),
+
+    /// The seal is too short for its selector.
+    Truncated(usize),
+
+    /// The selector names neither the mock verifier nor the expected Groth16 verifier.
+    Selector {
+        expected: [u8; SELECTOR_LEN],
+        actual: [u8; SELECTOR_LEN],
+    },
+
+    /// A mock seal commits to a different claim.
+    ClaimDigest,
+
+    /// The bytes after the selector are not a Groth16 seal.
+    Groth16(String),
+}
+
+impl fmt::Display for SealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SealError::Unsupported(kind) => write!(
+                f,
+                "{kind} receipts have no on-chain verifier; compress to Groth16 first"
+            ),
+            SealError::Truncated(len) => write!(f, "seal of {len} bytes is truncated"),
+            SealError::Selector { expected, actual } => write!(
+                f,
+                "seal selector {:#010x} does not match the verifier parameters selector {:#010x}",
+                u32::from_be_bytes(*actual),
+                u32::from_be_bytes(*expected)
+            ),
+            SealError::ClaimDigest => write!(f, "mock seal commits to a different claim"),
+            SealError::Groth16(err) => write!(f, "malformed Groth16 seal: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SealError {}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::Receipt;
+
+    use super::*;
+
+    fn fixture(path: &str) -> Receipt {
+        let path = format!("{}/../{path}", env!("CARGO_MANIFEST_DIR"));
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn round_trip(receipt: &Receipt, verifier_parameters: Digest) {
+        let seal = encode_seal(&receipt.inner).unwrap();
+        let decoded =
+            decode_seal_with_parameters(&seal, receipt.claim().unwrap(), verifier_parameters)
+                .unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&receipt.inner).unwrap()
+        );
+    }
+
+    #[test]
+    fn groth16_fixtures() {
+        for path in [
+            "json-validate/res/receipt_groth16.json",
+            "json-verify/res/receipt_groth16.json",
+            "encrypt-aesgcm/res/receipt_groth16.json",
+        ] {
+            let receipt = fixture(path);
+            let verifier_parameters = receipt.inner.groth16().unwrap().verifier_parameters;
+            round_trip(&receipt, verifier_parameters);
+        }
+
+        // json-validate writes seal.dat from its fixture receipt.
+        let receipt = fixture("json-validate/res/receipt_groth16.json");
+        let seal_dat = std::fs::read_to_string(format!(
+            "{}/../json-validate/res/seal.dat",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap();
+        assert_eq!(
+            hex::encode(encode_seal(&receipt.inner).unwrap()),
+            seal_dat.trim()
+        );
+        assert!(decode_seal(
+            &hex::decode(seal_dat.trim()).unwrap(),
+            receipt.claim().unwrap()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn fake() {
+        let claim = ReceiptClaim::ok(Digest::ZERO, b"journal".to_vec());
+        let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim.clone())), vec![]);
+        round_trip(&receipt, Digest::ZERO);
+
+        let seal = encode_seal(&receipt.inner).unwrap();
+        let other = ReceiptClaim::ok(Digest::ZERO, b"other".to_vec());
+        assert!(matches!(
+            decode_seal(&seal, other),
+            Err(SealError::ClaimDigest)
+        ));
+        assert!(matches!(
+            decode_seal(&[1, 2, 3, 4, 5], claim),
+            Err(SealError::Selector { .. })
+        ));
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A directory of receipts indexed by image ID, data hash and schema hash.
+//!
+//! Receipts are stored under their ID, the SHA-256 of their JSON encoding, so storing the same
+//! receipt twice keeps one copy and nothing is overwritten. Next to each receipt an [Entry]
+//! records what it is indexed by. The store does not decode journals: each tool fills in the
+//! [Index] from the journal of its own guest.
+//!
+//! Like [receipt_seal], this only depends on the verifier half of `risc0-zkvm`.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use risc0_zkp::verify::VerificationError;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const RECEIPT_SUFFIX: &str = ".json";
+const ENTRY_SUFFIX: &str = ".entry.json";
+
+/// What a receipt is indexed by.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Index {
+    /// Image ID of the guest that produced the receipt, which it is verified against.
+    pub image_id: [u8; 32],
+
+    /// Hash of the document the journal commits to, if any.
+    pub data_hash: Option<[u8; 32]>,
+
+    /// Hash of the schema the journal commits to, if any.
+    pub schema_hash: Option<[u8; 32]>,
+
+    /// Which guest produced the receipt, e.g. `check_schema`.
+    pub label: String,
+}
+
+impl Index {
+    /// An index without hashes, for the caller to fill in from the journal.
+    pub fn new(image_id: impl Into<risc0_zkvm::sha::Digest>, label: &str) -> Self {
+        Index {
+            image_id: image_id.into().into(),
+            data_hash: None,
+            schema_hash: None,
+            label: label.to_string(),
+        }
+    }
+}
+
+/// A stored receipt.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Entry {
+    /// SHA-256 of the receipt's JSON encoding, hex-encoded.
+    pub id: String,
+
+    /// What the receipt is indexed by.
+    pub index: Index,
+
+    /// When the receipt was first stored, in seconds since the Unix epoch.
+    pub stored_at: u64,
+}
+
+/// Selects entries by their [Index]. Fields that are `None` match every entry.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Query {
+    pub image_id: Option<[u8; 32]>,
+    pub data_hash: Option<[u8; 32]>,
+    pub schema_hash: Option<[u8; 32]>,
+}
+
+impl Query {
+    /// Whether `index` has every hash the query asks for.
+    pub fn matches(&self, index: &Index) -> bool {
+        [
+            (self.image_id, Some(index.image_id)),
+            (self.data_hash, index.data_hash),
+            (self.schema_hash, index.schema_hash),
+        ]
+        .iter()
+        .all(|(wanted, actual)| wanted.is_none() || wanted == actual)
+    }
+}
+
+/// A receipt store in a directory.
+#[derive(Clone, Debug)]
+pub struct ReceiptStore {
+    dir: PathBuf,
+}
+
+impl ReceiptStore {
+    /// Open the store in `dir`, creating the directory if needed.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(ReceiptStore { dir })
+    }
+
+    /// The store's directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Store `receipt` under `index`, returning its entry.
+    ///
+    /// A receipt that is already stored keeps its original entry. The receipt is not verified,
+    /// see [verify](Self::verify).
+    pub fn put(&self, receipt: &Receipt, index: Index) -> Result<Entry, StoreError> {
+        let receipt_json = serde_json::to_vec_pretty(receipt)?;
+        let id = hex::encode(Sha256::digest(&receipt_json));
+        if let Some(entry) = self.read_entry(&id)? {
+            return Ok(entry);
+        }
+
+        let stored_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let entry = Entry {
+            id,
+            index,
+            stored_at,
+        };
+        // The entry is written last, so every listed entry has its receipt.
+        fs::write(self.receipt_path(&entry.id), receipt_json)?;
+        fs::write(
+            self.entry_path(&entry.id),
+            serde_json::to_vec_pretty(&entry)?,
+        )?;
+        Ok(entry)
+    }
+
+    /// The entries matching `query`, oldest first.
+    pub fn list(&self, query: &Query) -> Result<Vec<Entry>, StoreError> {
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.dir)? {
+            let file_name = dir_entry?.file_name();
+            let Some(id) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix(ENTRY_SUFFIX))
+            else {
+                continue;
+            };
+            if let Some(entry) = self.read_entry(id)? {
+                if query.matches(&entry.index) {
+                    entries.push(entry);
+                }
+            }
+        }
+        entries.sort_by(|a, b| (a.stored_at, &a.id).cmp(&(b.stored_at, &b.id)));
+        Ok(entries)
+    }
+
+    /// The entry with ID `id`.
+    pub fn entry(&self, id: &str) -> Result<Entry, StoreError> {
+        self.read_entry(id)?
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))
+    }
+
+    /// The receipt with ID `id`, checked against its ID but not verified.
+    pub fn get(&self, id: &str) -> Result<Receipt, StoreError> {
+        let not_found = || StoreError::NotFound(id.to_string());
+        if !is_id(id) {
+            return Err(not_found());
+        }
+        let receipt_json = match fs::read(self.receipt_path(id)) {
+            Ok(receipt_json) => receipt_json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Err(not_found()),
+            Err(err) => return Err(err.into()),
+        };
+        if hex::encode(Sha256::digest(&receipt_json)) != id {
+            return Err(StoreError::Corrupt(id.to_string()));
+        }
+        Ok(serde_json::from_slice(&receipt_json)?)
+    }
+
+    /// Fetch the receipt with ID `id` and verify it against the image ID it is indexed by.
+    pub fn verify(&self, id: &str) -> Result<(Entry, Receipt), StoreError> {
+        let entry = self.entry(id)?;
+        let receipt = self.get(id)?;
+        receipt
+            .verify(entry.index.image_id)
+            .map_err(StoreError::Verify)?;
+        Ok((entry, receipt))
+    }
+
+    /// Path of the receipt with ID `id`, for tools that take a receipt file.
+    pub fn receipt_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}{RECEIPT_SUFFIX}"))
+    }
+
+    fn entry_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}{ENTRY_SUFFIX}"))
+    }
+
+    fn read_entry(&self, id: &str) -> Result<Option<Entry>, StoreError> {
+        if !is_id(id) {
+            return Ok(None);
+        }
+        match fs::read(self.entry_path(id)) {
+            Ok(entry_json) => Ok(Some(serde_json::from_slice(&entry_json)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// IDs are hex, so they cannot name a file outside the store.
+fn is_id(id: &str) -> bool {
+    !id.is_empty() && id.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Errors reading or writing a receipt store.
+#[derive(Debug)]
+pub enum StoreError {
+    /// The store directory could not be read or written.
+    Io(io::Error),
+
+    /// A receipt or entry could not be encoded or decoded.
+    Json(serde_json::Error),
+
+    /// No receipt has this ID.
+    NotFound(String),
+
+    /// The stored receipt no longer hashes to its ID.
+    Corrupt(String),
+
+    /// The receipt does not verify against the image ID it is indexed by.
+    Verify(VerificationError),
+}
+
+impl From<io::Error> for StoreError {
+    fn from(err: io::Error) -> Self {
+        StoreError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(err: serde_json::Error) -> Self {
+        StoreError::Json(err)
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(err) => write!(f, "receipt store: {err}"),
+            StoreError::Json(err) => write!(f, "receipt store: {err}"),
+            StoreError::NotFound(id) => write!(f, "no stored receipt {id}"),
+            StoreError::Corrupt(id) => write!(f, "stored receipt {id} does not match its ID"),
+            StoreError::Verify(err) => write!(f, "receipt does not verify: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+#[cfg(test)]
+mod tests {
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, ReceiptClaim};
+
+    use super::*;
+
+    fn fake_receipt(image_id: [u8; 32], journal: &[u8]) -> Receipt {
+        let claim = ReceiptClaim::ok(image_id, journal.to_vec());
+        Receipt::new(
+            InnerReceipt::Fake(FakeReceipt::new(claim)),
+            journal.to_vec(),
+        )
+    }
+
+    fn index(image_id: u8, data_hash: u8) -> Index {
+        Index {
+            image_id: [image_id; 32],
+            data_hash: Some([data_hash; 32]),
+            schema_hash: None,
+            label: "test".into(),
+        }
+    }
+
+    #[test]
+    fn put_and_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::open(dir.path()).unwrap();
+        let a = store
+            .put(&fake_receipt([1; 32], b"a"), index(1, 10))
+            .unwrap();
+        let b = store
+            .put(&fake_receipt([1; 32], b"b"), index(1, 11))
+            .unwrap();
+        let c = store
+            .put(&fake_receipt([2; 32], b"c"), index(2, 10))
+            .unwrap();
+
+        // Storing a receipt again keeps the first entry.
+        let again = store
+            .put(&fake_receipt([1; 32], b"a"), index(3, 12))
+            .unwrap();
+        assert_eq!(again, a);
+        assert_eq!(store.list(&Query::default()).unwrap().len(), 3);
+
+        let query = Query {
+            image_id: Some([1; 32]),
+            ..Default::default()
+        };
+        let mut ids: Vec<_> = store
+            .list(&query)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        ids.sort();
+        let mut expected = vec![a.id.clone(), b.id.clone()];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let query = Query {
+            data_hash: Some([10; 32]),
+            schema_hash: Some([0; 32]),
+            ..Default::default()
+        };
+        assert!(store.list(&query).unwrap().is_empty());
+
+        assert_eq!(store.entry(&c.id).unwrap(), c);
+        assert_eq!(store.get(&c.id).unwrap().journal.bytes, b"c");
+    }
+
+    #[test]
+    fn errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::open(dir.path()).unwrap();
+        let entry = store
+            .put(&fake_receipt([1; 32], b"a"), index(1, 10))
+            .unwrap();
+
+        // Fake receipts only verify in dev mode.
+        assert!(matches!(
+            store.verify(&entry.id),
+            Err(StoreError::Verify(_))
+        ));
+        assert!(matches!(store.get("00"), Err(StoreError::NotFound(_))));
+        assert!(matches!(
+            store.entry("../entry"),
+            Err(StoreError::NotFound(_))
+        ));
+        assert!(matches!(
+            store.get("../entry"),
+            Err(StoreError::NotFound(_))
+        ));
+
+        let receipt = fake_receipt([1; 32], b"b");
+        fs::write(
+            store.receipt_path(&entry.id),
+            serde_json::to_vec_pretty(&receipt).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(store.get(&entry.id), Err(StoreError::Corrupt(_))));
+    }
+}